@@ -0,0 +1,161 @@
+use crate::config::parse_finite;
+use crate::theme::Theme;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: lyrics_prompter [OPTIONS] [FILES...]
+
+Files ending in .lrc, .srt or .vtt are loaded as lyrics, .mp3, .flac, .ogg
+or .wav as audio, .setlist files as a setlist and .session files as a saved
+session.
+
+Options:
+  --fullscreen        Start in fullscreen
+  --autoplay          Start playback once the files are loaded
+  --offset <SECS>     Lyric offset in seconds, e.g. 0.3 or -0.25
+  --theme <NAME>      Theme for this run: dark, high-contrast or light
+  -h, --help          Print this help
+  -V, --version       Print the version";
+
+pub enum Command {
+    Run(Args),
+    Help,
+    Version,
+}
+
+#[derive(Default)]
+pub struct Args {
+    pub files: Vec<PathBuf>,
+    pub fullscreen: bool,
+    pub autoplay: bool,
+    pub offset: Option<f32>,
+    pub theme: Option<String>,
+}
+
+pub fn parse(mut args: impl Iterator<Item = OsString>) -> Result<Command, String> {
+    let mut parsed = Args::default();
+    while let Some(arg) = args.next() {
+        let arg = match arg.into_string() {
+            Ok(arg) => arg,
+            Err(file) => {
+                parsed.files.push(PathBuf::from(file));
+                continue;
+            }
+        };
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if arg.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| match inline.clone() {
+            Some(value) => Ok(value),
+            None => args
+                .next()
+                .ok_or_else(|| format!("{} needs a value", name))?
+                .into_string()
+                .map_err(|v| format!("invalid value '{}' for {}", v.to_string_lossy(), name)),
+        };
+        match flag.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-V" | "--version" => return Ok(Command::Version),
+            "--fullscreen" => parsed.fullscreen = true,
+            "--autoplay" => parsed.autoplay = true,
+            "--offset" => {
+                let offset = value("--offset")?;
+                let secs =
+                    parse_finite(&offset).ok_or_else(|| format!("invalid offset '{}'", offset))?;
+                parsed.offset = Some(secs);
+            }
+            "--theme" => {
+                let theme = value("--theme")?;
+                if Theme::named(&theme).is_none() {
+                    return Err(format!("unknown theme '{}'", theme));
+                }
+                parsed.theme = Some(theme.to_ascii_lowercase());
+            }
+            "--" => parsed.files.extend(args.by_ref().map(PathBuf::from)),
+            _ if flag.starts_with('-') && flag.len() > 1 => {
+                return Err(format!("unknown option '{}'", flag));
+            }
+            _ => parsed.files.push(PathBuf::from(arg)),
+        }
+    }
+    Ok(Command::Run(parsed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(args: &[&str]) -> Result<Args, String> {
+        match parse(args.iter().map(OsString::from))? {
+            Command::Run(args) => Ok(args),
+            _ => Err("not a run".to_string()),
+        }
+    }
+
+    #[test]
+    fn options() {
+        let args = run(&[
+            "--fullscreen",
+            "--offset=-0.25",
+            "--theme",
+            "Light",
+            "a.lrc",
+        ])
+        .unwrap();
+        assert!(args.fullscreen && !args.autoplay);
+        assert_eq!(args.offset, Some(-0.25));
+        assert_eq!(args.theme.as_deref(), Some("light"));
+        assert_eq!(args.files, [PathBuf::from("a.lrc")]);
+        let args = run(&["--autoplay", "--", "--odd.lrc", "b.mp3"]).unwrap();
+        assert!(args.autoplay);
+        assert_eq!(
+            args.files,
+            [PathBuf::from("--odd.lrc"), PathBuf::from("b.mp3")]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let cases = [
+            (&["--offset"][..], "--offset needs a value"),
+            (&["--offset", "soon"], "invalid offset 'soon'"),
+            (&["--offset", "inf"], "invalid offset 'inf'"),
+            (&["--offset=NaN"], "invalid offset 'NaN'"),
+            (&["--theme=neon"], "unknown theme 'neon'"),
+            (&["-x"], "unknown option '-x'"),
+        ];
+        for (args, message) in cases {
+            assert_eq!(run(args).err().as_deref(), Some(message));
+        }
+        assert!(matches!(
+            parse([OsString::from("-h")].into_iter()),
+            Ok(Command::Help)
+        ));
+        assert!(matches!(
+            parse([OsString::from("--version")].into_iter()),
+            Ok(Command::Version)
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8() {
+        use std::os::unix::ffi::OsStringExt;
+        let file = OsString::from_vec(b"caf\xe9.lrc".to_vec());
+        let parsed = parse([file.clone(), OsString::from("--autoplay")].into_iter());
+        let Ok(Command::Run(args)) = parsed else {
+            panic!("expected a run");
+        };
+        assert_eq!(args.files, [PathBuf::from(&file)]);
+        assert!(args.autoplay);
+        let parsed = parse([OsString::from("--theme"), file].into_iter());
+        assert_eq!(
+            parsed.err().as_deref(),
+            Some("invalid value 'caf\u{fffd}.lrc' for --theme")
+        );
+    }
+}
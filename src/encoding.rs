@@ -0,0 +1,90 @@
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+pub fn decode(bytes: &[u8]) -> (String, &'static str) {
+    let (encoding, body) = match Encoding::for_bom(bytes) {
+        Some((encoding, bom_len)) => (encoding, &bytes[bom_len..]),
+        None => (detect(bytes), bytes),
+    };
+    let (text, _) = encoding.decode_without_bom_handling(body);
+    (text.into_owned(), encoding.name())
+}
+
+pub fn has_bom(bytes: &[u8]) -> bool {
+    Encoding::for_bom(bytes).is_some()
+}
+
+pub fn encode(text: &str, name: &str, bom: bool) -> Option<Vec<u8>> {
+    let encoding = Encoding::for_label(name.as_bytes()).unwrap_or(UTF_8);
+    let text = match bom {
+        true => format!("\u{FEFF}{}", text),
+        false => text.to_string(),
+    };
+    if encoding == UTF_16LE {
+        return Some(text.encode_utf16().flat_map(u16::to_le_bytes).collect());
+    }
+    if encoding == UTF_16BE {
+        return Some(text.encode_utf16().flat_map(u16::to_be_bytes).collect());
+    }
+    let (bytes, _, had_errors) = encoding.encode(&text);
+    (!had_errors).then(|| bytes.into_owned())
+}
+
+fn detect(bytes: &[u8]) -> &'static Encoding {
+    if let Some(encoding) = detect_utf16(bytes) {
+        return encoding;
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return UTF_8;
+    }
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    detector.guess(None, true)
+}
+
+fn detect_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(512) & !1];
+    if sample.is_empty() {
+        return None;
+    }
+    let pairs = sample.len() / 2;
+    let even_zeros = sample.iter().step_by(2).filter(|b| **b == 0).count();
+    let odd_zeros = sample
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .filter(|b| **b == 0)
+        .count();
+    if odd_zeros * 2 > pairs && even_zeros * 10 < pairs {
+        Some(UTF_16LE)
+    } else if even_zeros * 2 > pairs && odd_zeros * 10 < pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        let text = "[ti:Caf\u{e9}]\r\n[00:01.00]Hello\r\n";
+        let cases = [
+            ("UTF-8", false),
+            ("UTF-8", true),
+            ("UTF-16LE", true),
+            ("UTF-16BE", false),
+        ];
+        for (name, bom) in cases {
+            let bytes = encode(text, name, bom).unwrap();
+            assert_eq!(has_bom(&bytes), bom, "{}", name);
+            assert_eq!(decode(&bytes), (text.to_string(), name), "{}", name);
+        }
+        let latin = encode(text, "windows-1252", false).unwrap();
+        assert_eq!(latin.len(), text.len() - 1);
+        assert_eq!(decode(&latin), (text.to_string(), "windows-1252"));
+        assert_eq!(encode("\u{3042}", "windows-1252", false), None);
+    }
+}
@@ -0,0 +1,248 @@
+use crate::lrc::{format_timestamp, LrcDocument, LrcLine};
+use std::fmt::Write;
+
+pub enum Format {
+    Lrc,
+    PlainLrc,
+    Srt,
+    Vtt,
+    Ass,
+}
+
+impl Format {
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "lrc" => Some(Self::Lrc),
+            "srt" => Some(Self::Srt),
+            "vtt" => Some(Self::Vtt),
+            "ass" => Some(Self::Ass),
+            _ => None,
+        }
+    }
+}
+
+pub fn export(doc: &LrcDocument, format: Format) -> String {
+    match format {
+        Format::Lrc => to_lrc(doc, true),
+        Format::PlainLrc => to_lrc(doc, false),
+        Format::Srt => to_srt(doc),
+        Format::Vtt => to_vtt(doc),
+        Format::Ass => to_ass(doc),
+    }
+}
+
+fn to_lrc(doc: &LrcDocument, words: bool) -> String {
+    let mut out = String::new();
+    let tags = [("ti", &doc.title), ("ar", &doc.artist), ("al", &doc.album)];
+    for (key, value) in tags {
+        if let Some(value) = value {
+            let _ = writeln!(out, "[{}:{}]", key, value);
+        }
+    }
+    if let Some(length) = doc.length {
+        let _ = writeln!(out, "[length:{}]", format_timestamp(length));
+    }
+    for (key, value) in &doc.tags {
+        let _ = writeln!(out, "[{}:{}]", key, value);
+    }
+    for line in &doc.lines {
+        let _ = write!(out, "[{}]", format_timestamp(line.time));
+        if !words || line.words.is_empty() {
+            out.push_str(&line.text);
+        } else {
+            for (i, word) in line.words.iter().enumerate() {
+                let _ = write!(out, "<{}>", format_timestamp(word.time));
+                out.push_str(&word_text(line, i));
+            }
+            if let Some(end) = line.end {
+                let _ = write!(out, "<{}>", format_timestamp(end));
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn to_srt(doc: &LrcDocument) -> String {
+    let mut out = String::new();
+    for (i, line) in doc.lines.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "{}\n{} --> {}\n{}\n",
+            i + 1,
+            clock(line.time, ','),
+            clock(line_end(&doc.lines, i), ','),
+            line.text
+        );
+    }
+    out
+}
+
+fn to_vtt(doc: &LrcDocument) -> String {
+    let mut out = String::from("WEBVTT\n\n");
+    for (i, line) in doc.lines.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "{} --> {}",
+            clock(line.time, '.'),
+            clock(line_end(&doc.lines, i), '.')
+        );
+        if line.words.is_empty() {
+            out.push_str(&escape_vtt(&line.text));
+        } else {
+            for (w, word) in line.words.iter().enumerate() {
+                if w > 0 || word.time > line.time {
+                    let _ = write!(out, "<{}>", clock(word.time, '.'));
+                }
+                out.push_str(&escape_vtt(&word_text(line, w)));
+            }
+        }
+        out.push_str("\n\n");
+    }
+    out
+}
+
+fn to_ass(doc: &LrcDocument) -> String {
+    let mut out = String::from("[Script Info]\nScriptType: v4.00+\n");
+    if let Some(title) = doc.display_name() {
+        let _ = writeln!(out, "Title: {}", title);
+    }
+    out.push_str(
+        "\n[V4+ Styles]\n\
+         Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, \
+         BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, \
+         BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding\n\
+         Style: Default,Arial,48,&H00FFFFFF,&H00808080,&H00000000,&H00000000,-1,0,0,0,\
+         100,100,0,0,1,2,0,2,10,10,30,1\n\
+         \n[Events]\n\
+         Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n",
+    );
+    for (i, line) in doc.lines.iter().enumerate() {
+        let end = line_end(&doc.lines, i);
+        let mut text = String::new();
+        if line.words.is_empty() {
+            text.push_str(&line.text);
+        } else {
+            if line.words[0].time > line.time {
+                let _ = write!(text, "{{\\k{}}}", centis(line.words[0].time - line.time));
+            }
+            for (w, word) in line.words.iter().enumerate() {
+                let next = line.words.get(w + 1).map_or(end, |n| n.time);
+                let _ = write!(text, "{{\\k{}}}", centis(next - word.time));
+                text.push_str(&word_text(line, w));
+            }
+        }
+        let _ = writeln!(
+            out,
+            "Dialogue: 0,{},{},Default,,0,0,0,,{}",
+            ass_clock(line.time),
+            ass_clock(end),
+            text.replace('\n', "\\N")
+        );
+    }
+    out
+}
+
+fn line_end(lines: &[LrcLine], idx: usize) -> f32 {
+    let line = &lines[idx];
+    let next = lines.get(idx + 1).map(|n| n.time);
+    match (line.end, next) {
+        (Some(end), _) => end,
+        (None, Some(next)) if next > line.time => next.min(line.time + 10.0),
+        _ => line.time + 5.0,
+    }
+}
+
+fn word_text(line: &LrcLine, idx: usize) -> String {
+    let start = line.words[idx].start;
+    let end = line.words.get(idx + 1).map_or(usize::MAX, |w| w.start);
+    line.text
+        .chars()
+        .skip(start)
+        .take(end.saturating_sub(start))
+        .collect()
+}
+
+fn escape_vtt(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn clock(secs: f32, sep: char) -> String {
+    let ms = (secs.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        sep,
+        ms % 1000
+    )
+}
+
+fn ass_clock(secs: f32) -> String {
+    let cs = centis(secs);
+    format!(
+        "{}:{:02}:{:02}.{:02}",
+        cs / 360_000,
+        cs / 6000 % 60,
+        cs / 100 % 60,
+        cs % 100
+    )
+}
+
+fn centis(secs: f32) -> u64 {
+    (secs.max(0.0) * 100.0).round() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lrc::parse_lrc;
+    use crate::subtitle::{parse_srt, parse_vtt};
+
+    const SOURCE: &str = "[ti:Song]\n[ar:Band]\n\
+        [00:01.00]<00:01.00>Hello <00:01.50>world<00:02.00>\n\
+        [00:03.00]Plain & simple\n";
+
+    fn lines(doc: &LrcDocument) -> Vec<(String, String)> {
+        doc.lines
+            .iter()
+            .map(|l| (format_timestamp(l.time), l.text.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn round_trips() {
+        let doc = parse_lrc(SOURCE);
+        let lrc = parse_lrc(&export(&doc, Format::Lrc));
+        assert_eq!(lines(&lrc), lines(&doc));
+        assert_eq!(lrc.lines[0].words.len(), 2);
+        assert_eq!(lrc.title.as_deref(), Some("Song"));
+        let plain = parse_lrc(&export(&doc, Format::PlainLrc));
+        assert_eq!(lines(&plain), lines(&doc));
+        assert!(plain.lines[0].words.is_empty());
+        for parsed in [
+            parse_srt(&export(&doc, Format::Srt)),
+            parse_vtt(&export(&doc, Format::Vtt)),
+        ] {
+            assert_eq!(lines(&parsed), lines(&doc));
+            assert_eq!(parsed.lines[0].end, Some(2.0));
+        }
+    }
+
+    #[test]
+    fn ass() {
+        let ass = export(&parse_lrc(SOURCE), Format::Ass);
+        assert!(ass.contains("Title: Band - Song\n"));
+        let dialogue: Vec<_> = ass.lines().filter(|l| l.starts_with("Dialogue:")).collect();
+        assert_eq!(
+            dialogue,
+            [
+                "Dialogue: 0,0:00:01.00,0:00:02.00,Default,,0,0,0,,{\\k50}Hello {\\k50}world",
+                "Dialogue: 0,0:00:03.00,0:00:08.00,Default,,0,0,0,,Plain & simple",
+            ]
+        );
+    }
+}
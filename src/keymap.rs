@@ -0,0 +1,342 @@
+use crate::config::{parse_entries, read_config};
+use minifb::{Key, KeyRepeat, Window};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    LoadLyrics,
+    LoadAudio,
+    Recent,
+    OutputDevice,
+    VolumeUp,
+    VolumeDown,
+    Mute,
+    LoadSession,
+    SaveSession,
+    LoadSetlist,
+    SaveSetlist,
+    AddToSetlist,
+    NextSong,
+    PrevSong,
+    AutoAdvance,
+    SaveAs,
+    TapSync,
+    Play,
+    Pause,
+    PlayPause,
+    Stop,
+    Fullscreen,
+    ExitFullscreen,
+    CycleTheme,
+    PrevLine,
+    NextLine,
+    RestartLine,
+    OffsetUp,
+    OffsetDown,
+    OffsetUpFine,
+    OffsetDownFine,
+    SaveOffset,
+    LoopA,
+    LoopB,
+    LoopToggle,
+    LoopLine,
+    LoopClear,
+    SpeedUp,
+    SpeedDown,
+    SpeedReset,
+    LookAheadUp,
+    LookAheadDown,
+    PreRollUp,
+    PreRollDown,
+    Tap,
+    Untap,
+}
+
+const ACTIONS: &[(&str, Action)] = &[
+    ("load_lyrics", Action::LoadLyrics),
+    ("load_audio", Action::LoadAudio),
+    ("recent", Action::Recent),
+    ("output_device", Action::OutputDevice),
+    ("volume_up", Action::VolumeUp),
+    ("volume_down", Action::VolumeDown),
+    ("mute", Action::Mute),
+    ("load_session", Action::LoadSession),
+    ("save_session", Action::SaveSession),
+    ("load_setlist", Action::LoadSetlist),
+    ("save_setlist", Action::SaveSetlist),
+    ("add_to_setlist", Action::AddToSetlist),
+    ("next_song", Action::NextSong),
+    ("prev_song", Action::PrevSong),
+    ("auto_advance", Action::AutoAdvance),
+    ("save_as", Action::SaveAs),
+    ("tap_sync", Action::TapSync),
+    ("play", Action::Play),
+    ("pause", Action::Pause),
+    ("play_pause", Action::PlayPause),
+    ("stop", Action::Stop),
+    ("fullscreen", Action::Fullscreen),
+    ("exit_fullscreen", Action::ExitFullscreen),
+    ("cycle_theme", Action::CycleTheme),
+    ("prev_line", Action::PrevLine),
+    ("next_line", Action::NextLine),
+    ("restart_line", Action::RestartLine),
+    ("offset_up", Action::OffsetUp),
+    ("offset_down", Action::OffsetDown),
+    ("offset_up_fine", Action::OffsetUpFine),
+    ("offset_down_fine", Action::OffsetDownFine),
+    ("save_offset", Action::SaveOffset),
+    ("loop_a", Action::LoopA),
+    ("loop_b", Action::LoopB),
+    ("loop_toggle", Action::LoopToggle),
+    ("loop_line", Action::LoopLine),
+    ("loop_clear", Action::LoopClear),
+    ("speed_up", Action::SpeedUp),
+    ("speed_down", Action::SpeedDown),
+    ("speed_reset", Action::SpeedReset),
+    ("look_ahead_up", Action::LookAheadUp),
+    ("look_ahead_down", Action::LookAheadDown),
+    ("pre_roll_up", Action::PreRollUp),
+    ("pre_roll_down", Action::PreRollDown),
+    ("tap", Action::Tap),
+    ("untap", Action::Untap),
+];
+
+const KEYS: &[Key] = &[
+    Key::A,
+    Key::B,
+    Key::C,
+    Key::D,
+    Key::E,
+    Key::F,
+    Key::G,
+    Key::H,
+    Key::I,
+    Key::J,
+    Key::K,
+    Key::L,
+    Key::M,
+    Key::N,
+    Key::O,
+    Key::P,
+    Key::Q,
+    Key::R,
+    Key::S,
+    Key::T,
+    Key::U,
+    Key::V,
+    Key::W,
+    Key::X,
+    Key::Y,
+    Key::Z,
+    Key::Key0,
+    Key::Key1,
+    Key::Key2,
+    Key::Key3,
+    Key::Key4,
+    Key::Key5,
+    Key::Key6,
+    Key::Key7,
+    Key::Key8,
+    Key::Key9,
+    Key::F1,
+    Key::F2,
+    Key::F3,
+    Key::F4,
+    Key::F5,
+    Key::F6,
+    Key::F7,
+    Key::F8,
+    Key::F9,
+    Key::F10,
+    Key::F11,
+    Key::F12,
+    Key::Up,
+    Key::Down,
+    Key::Left,
+    Key::Right,
+    Key::Space,
+    Key::Enter,
+    Key::Escape,
+    Key::Backspace,
+    Key::Tab,
+    Key::Delete,
+    Key::Insert,
+    Key::Home,
+    Key::End,
+    Key::PageUp,
+    Key::PageDown,
+    Key::Minus,
+    Key::Equal,
+    Key::Comma,
+    Key::Period,
+    Key::Slash,
+    Key::Semicolon,
+    Key::Apostrophe,
+    Key::LeftBracket,
+    Key::RightBracket,
+    Key::Backslash,
+    Key::Backquote,
+    Key::Pause,
+    Key::NumPad0,
+    Key::NumPad1,
+    Key::NumPad2,
+    Key::NumPad3,
+    Key::NumPad4,
+    Key::NumPad5,
+    Key::NumPad6,
+    Key::NumPad7,
+    Key::NumPad8,
+    Key::NumPad9,
+    Key::NumPadPlus,
+    Key::NumPadMinus,
+    Key::NumPadEnter,
+    Key::NumPadDot,
+    Key::NumPadSlash,
+    Key::NumPadAsterisk,
+];
+
+pub struct Keymap {
+    bindings: Vec<(Key, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: vec![
+                (Key::Space, Action::PlayPause),
+                (Key::S, Action::Stop),
+                (Key::F, Action::Fullscreen),
+                (Key::F11, Action::Fullscreen),
+                (Key::Escape, Action::ExitFullscreen),
+                (Key::T, Action::CycleTheme),
+                (Key::Left, Action::PrevLine),
+                (Key::Right, Action::NextLine),
+                (Key::Up, Action::RestartLine),
+                (Key::R, Action::RestartLine),
+                (Key::Equal, Action::OffsetUp),
+                (Key::NumPadPlus, Action::OffsetUp),
+                (Key::Minus, Action::OffsetDown),
+                (Key::NumPadMinus, Action::OffsetDown),
+                (Key::RightBracket, Action::OffsetUpFine),
+                (Key::LeftBracket, Action::OffsetDownFine),
+                (Key::O, Action::SaveOffset),
+                (Key::B, Action::LoopToggle),
+                (Key::K, Action::LoopLine),
+                (Key::X, Action::SpeedUp),
+                (Key::Z, Action::SpeedDown),
+                (Key::C, Action::SpeedReset),
+                (Key::Period, Action::LookAheadUp),
+                (Key::Comma, Action::LookAheadDown),
+                (Key::L, Action::LoadLyrics),
+                (Key::A, Action::LoadAudio),
+                (Key::H, Action::Recent),
+                (Key::D, Action::OutputDevice),
+                (Key::Key0, Action::VolumeUp),
+                (Key::Key9, Action::VolumeDown),
+                (Key::M, Action::Mute),
+                (Key::PageDown, Action::NextSong),
+                (Key::PageUp, Action::PrevSong),
+                (Key::Insert, Action::AddToSetlist),
+                (Key::F5, Action::SaveSession),
+                (Key::F6, Action::SaveSetlist),
+                (Key::N, Action::AutoAdvance),
+                (Key::Enter, Action::Tap),
+                (Key::Backspace, Action::Untap),
+            ],
+        }
+    }
+}
+
+impl Keymap {
+    pub fn load() -> Self {
+        let mut keymap = Self::default();
+        if let Some(content) = read_config("keys.conf") {
+            keymap.apply(&content);
+        }
+        keymap
+    }
+
+    fn apply(&mut self, content: &str) {
+        for (name, keys) in parse_entries(content) {
+            let Some(action) = action_from_name(name) else {
+                continue;
+            };
+            self.bindings.retain(|(_, a)| *a != action);
+            for key in keys.split(',').filter_map(key_from_name) {
+                self.bindings.push((key, action));
+            }
+        }
+    }
+
+    pub fn pressed(&self, window: &Window) -> Vec<Action> {
+        self.bindings
+            .iter()
+            .filter(|(key, _)| window.is_key_pressed(*key, KeyRepeat::No))
+            .map(|(_, action)| *action)
+            .collect()
+    }
+}
+
+fn action_from_name(name: &str) -> Option<Action> {
+    ACTIONS
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, action)| *action)
+}
+
+fn key_from_name(name: &str) -> Option<Key> {
+    let name = name.trim();
+    let name = match name {
+        "0" | "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" => format!("Key{}", name),
+        "+" => "Equal".to_string(),
+        "-" => "Minus".to_string(),
+        _ => name.to_string(),
+    };
+    KEYS.iter()
+        .find(|key| format!("{:?}", key).eq_ignore_ascii_case(&name))
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(keymap: &Keymap, action: Action) -> Vec<Key> {
+        keymap
+            .bindings
+            .iter()
+            .filter(|(_, a)| *a == action)
+            .map(|(key, _)| *key)
+            .collect()
+    }
+
+    #[test]
+    fn key_names() {
+        let cases = [
+            ("Space", Some(Key::Space)),
+            (" f11 ", Some(Key::F11)),
+            ("7", Some(Key::Key7)),
+            ("+", Some(Key::Equal)),
+            ("-", Some(Key::Minus)),
+            ("numpadplus", Some(Key::NumPadPlus)),
+            ("Hyper", None),
+            ("", None),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(key_from_name(name), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn overrides() {
+        let mut keymap = Keymap::default();
+        keymap.apply(
+            "# comment\nPLAY_PAUSE = Space, P\nnext_line = Down, Bogus\n\
+             stop =\nwarp_drive = W\nprev_line\n",
+        );
+        assert_eq!(keys(&keymap, Action::PlayPause), [Key::Space, Key::P]);
+        assert_eq!(keys(&keymap, Action::NextLine), [Key::Down]);
+        assert_eq!(keys(&keymap, Action::Stop), []);
+        assert_eq!(keys(&keymap, Action::PrevLine), [Key::Left]);
+        assert_eq!(keys(&keymap, Action::Fullscreen), [Key::F, Key::F11]);
+    }
+}
@@ -0,0 +1,482 @@
+#[derive(Clone)]
+pub struct LrcLine {
+    pub time: f32,
+    pub text: String,
+    pub words: Vec<LrcWord>,
+    pub end: Option<f32>,
+    pub source: usize,
+}
+
+#[derive(Clone)]
+pub struct LrcWord {
+    pub time: f32,
+    pub start: usize,
+}
+
+impl LrcLine {
+    pub fn new(time: f32, text: &str, source: usize) -> Self {
+        let (text, words, end) = parse_words(text, time);
+        Self {
+            time,
+            text,
+            words,
+            end,
+            source,
+        }
+    }
+
+    fn shifted(&self, delta: f32) -> Self {
+        Self {
+            time: self.time + delta,
+            text: self.text.clone(),
+            words: self
+                .words
+                .iter()
+                .map(|w| LrcWord {
+                    time: w.time + delta,
+                    start: w.start,
+                })
+                .collect(),
+            end: self.end.map(|e| e + delta),
+            source: self.source,
+        }
+    }
+
+    pub fn sung_chars(&self, time: f32, next_time: Option<f32>) -> f32 {
+        let total = self.text.chars().count();
+        let Some(first) = self.words.first() else {
+            return total as f32;
+        };
+        if time < first.time {
+            return 0.0;
+        }
+        let i = self.words.iter().rposition(|w| time >= w.time).unwrap_or(0);
+        let word = &self.words[i];
+        let (end_time, end_char) = match self.words.get(i + 1) {
+            Some(next) => (next.time, next.start),
+            None => (self.end.or(next_time).unwrap_or(word.time + 1.0), total),
+        };
+        let frac = if end_time > word.time {
+            ((time - word.time) / (end_time - word.time)).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        word.start as f32 + frac * end_char.saturating_sub(word.start) as f32
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct LrcDocument {
+    pub lines: Vec<LrcLine>,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub length: Option<f32>,
+    pub offset_ms: i32,
+    pub tags: Vec<(String, String)>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl LrcDocument {
+    pub fn display_name(&self) -> Option<String> {
+        match (&self.artist, &self.title) {
+            (Some(artist), Some(title)) => Some(format!("{} - {}", artist, title)),
+            (None, Some(title)) => Some(title.clone()),
+            (Some(artist), None) => Some(artist.clone()),
+            (None, None) => None,
+        }
+    }
+
+    pub fn diagnostics(&self, audio_length: Option<f32>) -> Vec<Diagnostic> {
+        let mut out = self.diagnostics.clone();
+        if let Some(length) = audio_length.or(self.length) {
+            for line in self.lines.iter().filter(|l| l.time > length) {
+                out.push(Diagnostic {
+                    line: line.source,
+                    message: format!(
+                        "starts at {}, past the end of the audio ({})",
+                        format_timestamp(line.time),
+                        format_timestamp(length)
+                    ),
+                });
+            }
+        }
+        out.sort_by_key(|d| d.line);
+        out
+    }
+
+    pub fn normalize(&mut self) {
+        self.lines.sort_by(|a, b| a.time.total_cmp(&b.time));
+        let mut kept: Vec<LrcLine> = Vec::with_capacity(self.lines.len());
+        for line in std::mem::take(&mut self.lines) {
+            if let Some(prev) = kept.last() {
+                if prev.time == line.time {
+                    if prev.text == line.text {
+                        let message = format!("duplicate of line {}, removed", prev.source);
+                        self.warn(line.source, &message);
+                        continue;
+                    }
+                    let message = format!("same timestamp as line {}", prev.source);
+                    self.warn(line.source, &message);
+                } else if prev.end.is_some_and(|end| end > line.time) {
+                    let message = format!("word timing overlaps line {}", line.source);
+                    self.warn(prev.source, &message);
+                }
+            }
+            kept.push(line);
+        }
+        self.lines = kept;
+    }
+
+    pub fn shift(&mut self, delta: f32) {
+        for line in &mut self.lines {
+            line.time = (line.time + delta).max(0.0);
+            for word in &mut line.words {
+                word.time = (word.time + delta).max(0.0);
+            }
+            if let Some(end) = &mut line.end {
+                *end = (*end + delta).max(0.0);
+            }
+        }
+    }
+
+    pub fn warn(&mut self, line: usize, message: &str) {
+        self.diagnostics.push(Diagnostic {
+            line,
+            message: message.to_string(),
+        });
+    }
+
+    fn set_tag(&mut self, key: &str, value: &str) -> bool {
+        if value.is_empty() {
+            return true;
+        }
+        match key.to_ascii_lowercase().as_str() {
+            "ti" => self.title = Some(value.to_string()),
+            "ar" => self.artist = Some(value.to_string()),
+            "al" => self.album = Some(value.to_string()),
+            "length" => {
+                self.length = parse_timestamp(value);
+                return self.length.is_some();
+            }
+            "offset" => match value.trim_start_matches('+').parse() {
+                Ok(offset) => self.offset_ms = offset,
+                Err(_) => return false,
+            },
+            _ => self.tags.push((key.to_string(), value.to_string())),
+        }
+        true
+    }
+}
+
+#[derive(Clone)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+
+pub fn parse_lrc(content: &str) -> LrcDocument {
+    let mut doc = LrcDocument::default();
+    let mut last_time = 0.0f32;
+    for (num, line) in content.lines().enumerate() {
+        let num = num + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if !line.starts_with('[') {
+            doc.warn(num, "no timestamp, line skipped");
+            continue;
+        }
+        let Some(end) = line.find(']') else {
+            doc.warn(num, "unclosed bracket, line skipped");
+            continue;
+        };
+        let tag = &line[1..end];
+        if parse_timestamp(tag).is_some() {
+            let (times, text) = split_timestamps(line);
+            if times[0] < last_time {
+                doc.warn(num, "timestamp earlier than a previous line");
+            }
+            last_time = times[0];
+            if !text.is_empty() {
+                let base = LrcLine::new(times[0], text, num);
+                for time in &times[1..] {
+                    doc.lines.push(base.shifted(time - times[0]));
+                }
+                doc.lines.push(base);
+            }
+        } else if let Some((key, value)) = tag.split_once(':') {
+            let key = key.trim();
+            if !key.is_empty() && key.chars().all(|c| c.is_ascii_digit()) {
+                doc.warn(num, &format!("malformed timestamp [{}], line skipped", tag));
+            } else if !doc.set_tag(key, value.trim()) {
+                doc.warn(num, &format!("invalid value for [{}:]", key));
+            }
+        } else {
+            doc.warn(num, &format!("unrecognised tag [{}], line skipped", tag));
+        }
+    }
+    doc.shift(-doc.offset_ms as f32 / 1000.0);
+    doc.normalize();
+    doc
+}
+
+pub fn set_offset_tag(content: &str, offset_ms: i32) -> String {
+    let tag = format!("[offset:{:+}]", offset_ms);
+    let newline = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut out = vec![];
+    let mut replaced = false;
+    for line in content.lines() {
+        let lower = line.trim().to_ascii_lowercase();
+        if lower.starts_with("[offset:") {
+            if !replaced {
+                out.push(tag.clone());
+                replaced = true;
+            }
+        } else {
+            out.push(line.to_string());
+        }
+    }
+    if !replaced {
+        let header = out
+            .iter()
+            .take_while(|l| {
+                let l = l.trim();
+                l.starts_with('[')
+                    && l.find(']')
+                        .is_some_and(|e| parse_timestamp(&l[1..e]).is_none())
+            })
+            .count();
+        out.insert(header, tag);
+    }
+    let mut text = out.join(newline);
+    if content.is_empty() || content.ends_with('\n') {
+        text.push_str(newline);
+    }
+    text
+}
+
+fn split_timestamps(line: &str) -> (Vec<f32>, &str) {
+    let mut times = vec![];
+    let mut rest = line;
+    while let Some(inner) = rest.strip_prefix('[') {
+        let Some(end) = inner.find(']') else { break };
+        let Some(time) = parse_timestamp(&inner[..end]) else {
+            break;
+        };
+        times.push(time);
+        rest = inner[end + 1..].trim_start();
+    }
+    (times, rest.trim())
+}
+
+fn parse_words(text: &str, line_time: f32) -> (String, Vec<LrcWord>, Option<f32>) {
+    let mut plain = String::new();
+    let mut words = vec![];
+    let mut time = line_time;
+    let mut rest = text;
+    let mut tagged = false;
+    loop {
+        let tag = next_word_tag(rest);
+        let segment = tag.map_or(rest, |(start, _, _)| &rest[..start]);
+        let had_text = push_segment(&mut plain, &mut words, segment, time);
+        match tag {
+            Some((_, end, next)) => {
+                tagged = true;
+                time = next;
+                rest = &rest[end..];
+            }
+            None => {
+                if !tagged {
+                    return (text.to_string(), vec![], None);
+                }
+                while plain.ends_with(' ') {
+                    plain.pop();
+                }
+                let end = (!had_text && !words.is_empty()).then_some(time);
+                return (plain, words, end);
+            }
+        }
+    }
+}
+
+fn next_word_tag(text: &str) -> Option<(usize, usize, f32)> {
+    let mut from = 0;
+    while let Some(pos) = text[from..].find('<') {
+        let start = from + pos;
+        if let Some(len) = text[start..].find('>') {
+            if let Some(time) = parse_timestamp(&text[start + 1..start + len]) {
+                return Some((start, start + len + 1, time));
+            }
+        }
+        from = start + 1;
+    }
+    None
+}
+
+fn push_segment(plain: &mut String, words: &mut Vec<LrcWord>, segment: &str, time: f32) -> bool {
+    let trimmed = segment.trim_start();
+    if trimmed.len() != segment.len() && !plain.is_empty() && !plain.ends_with(' ') {
+        plain.push(' ');
+    }
+    if trimmed.is_empty() {
+        return false;
+    }
+    words.push(LrcWord {
+        time,
+        start: plain.chars().count(),
+    });
+    for ch in trimmed.chars() {
+        if !ch.is_whitespace() {
+            plain.push(ch);
+        } else if !plain.ends_with(' ') {
+            plain.push(' ');
+        }
+    }
+    true
+}
+
+pub fn parse_timestamp(ts: &str) -> Option<f32> {
+    let parts: Vec<&str> = ts.trim().split(':').collect();
+    match parts[..] {
+        [mins, secs] => Some(parse_digits(mins)? * 60.0 + parse_seconds(secs)?),
+        [hours, mins, secs] if secs.contains('.') => {
+            let mins = parse_digits(mins)?;
+            if mins >= 60.0 {
+                return None;
+            }
+            Some(parse_digits(hours)? * 3600.0 + mins * 60.0 + parse_seconds(secs)?)
+        }
+        [mins, secs, frac] => {
+            let frac_value = parse_digits(frac)? / 10f32.powi(frac.len() as i32);
+            Some(parse_digits(mins)? * 60.0 + parse_digits(secs)? + frac_value)
+        }
+        _ => None,
+    }
+}
+
+fn parse_digits(text: &str) -> Option<f32> {
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+fn parse_seconds(text: &str) -> Option<f32> {
+    match text.split_once('.') {
+        Some((whole, frac)) => {
+            let frac_value = parse_digits(frac)? / 10f32.powi(frac.len() as i32);
+            Some(parse_digits(whole)? + frac_value)
+        }
+        None => parse_digits(text),
+    }
+}
+
+pub fn format_timestamp(secs: f32) -> String {
+    let centis = (secs.max(0.0) * 100.0).round() as u32;
+    format!(
+        "{:02}:{:02}.{:02}",
+        centis / 6000,
+        centis / 100 % 60,
+        centis % 100
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamps() {
+        let cases = [
+            ("01:02", Some(62.0)),
+            ("01:02.5", Some(62.5)),
+            ("01:02.50", Some(62.5)),
+            ("01:02.345", Some(62.345)),
+            ("01:02:03", Some(62.03)),
+            ("1:02:03.50", Some(3723.5)),
+            ("1:60:03.50", None),
+            ("01:xx", None),
+            ("01", None),
+            ("", None),
+        ];
+        for (text, expected) in cases {
+            let parsed = parse_timestamp(text);
+            match (parsed, expected) {
+                (Some(a), Some(b)) => assert!((a - b).abs() < 1e-3, "{}: {} != {}", text, a, b),
+                _ => assert_eq!(parsed, expected, "{}", text),
+            }
+        }
+    }
+
+    #[test]
+    fn repeated_timestamps() {
+        let doc = parse_lrc("[00:10.00][00:30.00]Chorus\n[00:20.00]Verse\n");
+        let lines: Vec<_> = doc
+            .lines
+            .iter()
+            .map(|l| (l.time, l.text.as_str()))
+            .collect();
+        assert_eq!(lines, [(10.0, "Chorus"), (20.0, "Verse"), (30.0, "Chorus")]);
+    }
+
+    #[test]
+    fn offset_tag() {
+        let doc = parse_lrc("[offset:+500]\n[00:10.00]Late\n[00:00.20]Early\n");
+        assert_eq!(doc.offset_ms, 500);
+        let times: Vec<_> = doc.lines.iter().map(|l| l.time).collect();
+        assert_eq!(times, [0.0, 9.5]);
+    }
+
+    #[test]
+    fn offset_tag_rewrite() {
+        let cases = [
+            (
+                "[ti:A]\n[00:01.00]x\n",
+                "[ti:A]\n[offset:+250]\n[00:01.00]x\n",
+            ),
+            (
+                "[ti:A]\r\n[00:01.00]x",
+                "[ti:A]\r\n[offset:+250]\r\n[00:01.00]x",
+            ),
+            (
+                "[offset:-5]\r\n[OFFSET:9]\r\n[00:01.00]x\r\n",
+                "[offset:+250]\r\n[00:01.00]x\r\n",
+            ),
+            ("", "[offset:+250]\n"),
+        ];
+        for (content, expected) in cases {
+            assert_eq!(set_offset_tag(content, 250), expected);
+        }
+    }
+
+    #[test]
+    fn word_timings() {
+        let doc = parse_lrc("[00:01.00]<00:01.00>Hello <00:01.50>world<00:02.00>\n");
+        let line = &doc.lines[0];
+        assert_eq!(line.text, "Hello world");
+        let words: Vec<_> = line.words.iter().map(|w| (w.time, w.start)).collect();
+        assert_eq!(words, [(1.0, 0), (1.5, 6)]);
+        assert_eq!(line.end, Some(2.0));
+    }
+
+    #[test]
+    fn diagnostics() {
+        let doc = parse_lrc("[ti:Song]\nno tag\n[00:xx]bad\n[00:05.00]Ok\n");
+        let lines: Vec<_> = doc.diagnostics.iter().map(|d| d.line).collect();
+        assert_eq!(doc.title.as_deref(), Some("Song"));
+        assert_eq!(lines, [2, 3]);
+        assert_eq!(doc.lines.len(), 1);
+    }
+}
@@ -1,21 +1,52 @@
 #![windows_subsystem = "windows"]
+#![allow(clippy::too_many_arguments)]
 
+mod cli;
+mod config;
+mod encoding;
+mod export;
+mod keymap;
+mod lrc;
+mod session;
+mod setlist;
+mod settings;
+mod stretch;
+mod subtitle;
+mod sync;
+mod theme;
+
+use export::{export, Format};
+use keymap::{Action, Keymap};
+use lrc::{parse_lrc, set_offset_tag, Diagnostic, LrcDocument, LrcLine};
 #[cfg(target_os = "linux")]
 use minifb::Icon;
-use minifb::{Key, MouseButton, MouseMode, Window, WindowOptions};
-use rodio::{stream::OutputStream, stream::OutputStreamBuilder, Decoder, Sink};
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
+use rfd::{MessageButtons, MessageDialogResult};
+use rodio::cpal::{self, traits::HostTrait};
+use rodio::{
+    stream::OutputStream, stream::OutputStreamBuilder, Decoder, DeviceTrait, Sink, Source,
+};
 use rusttype::{point, Font, Scale};
-use std::{fs::File, io::BufReader, path::PathBuf, sync::Arc, time::Instant};
-
-const BG: u32 = 0x121212;
-const WHITE: u32 = 0xf0f0f0;
-const GRAY: u32 = 0x606060;
-const ACCENT: u32 = 0x909090;
-const BTN_BG: u32 = 0x1e1e1e;
-const BAR_BG: u32 = 0x252525;
-const BAR_FG: u32 = 0x707070;
-const GREEN: u32 = 0x4a9f4a;
+use session::Session;
+use setlist::{Setlist, SetlistEntry};
+use settings::{Settings, SongSettings};
+use std::{
+    cell::Cell,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use stretch::{StretchHandle, TimeStretch};
+use subtitle::{parse_srt, parse_vtt};
+use sync::TapSync;
+use theme::Theme;
 
+const LYRIC_EXTENSIONS: &[&str] = &["lrc", "srt", "vtt"];
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "flac", "ogg", "wav"];
+const SEEK_MARGIN: f32 = 0.001;
+const LOOP_TAIL: f32 = 0.25;
 const FONT_DATA: &[u8] = include_bytes!("../assets/font.ttf");
 #[cfg(target_os = "linux")]
 const ICON_ARGB: &[u64] = include!("../assets/icon_argb.rs");
@@ -23,11 +54,32 @@ const ICON_ARGB: &[u64] = include!("../assets/icon_argb.rs");
 const ICON_ICO: &[u8] = include_bytes!("../assets/icon.ico");
 
 fn main() {
-    let font = Font::try_from_bytes(FONT_DATA);
+    let args = match cli::parse(std::env::args_os().skip(1)) {
+        Ok(cli::Command::Run(args)) => args,
+        Ok(cli::Command::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Ok(cli::Command::Version) => {
+            println!("lyrics_prompter {}", env!("CARGO_PKG_VERSION"));
+            return;
+        }
+        Err(err) => {
+            eprintln!("lyrics_prompter: {}\n\n{}", err, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
     let mut app = App::new();
-    let mut width = 1024usize;
-    let mut height = 600usize;
-    let mut buffer: Vec<u32> = vec![BG; width * height];
+    let font = app
+        .settings
+        .font
+        .as_ref()
+        .and_then(|path| std::fs::read(path).ok())
+        .and_then(Font::try_from_vec)
+        .or_else(|| Font::try_from_bytes(FONT_DATA));
+    let mut width = app.settings.width;
+    let mut height = app.settings.height;
+    let mut buffer: Vec<u32> = vec![app.theme().bg; width * height];
     let mut window = Window::new(
         "Lyrics Prompter",
         width,
@@ -46,26 +98,120 @@ fn main() {
     #[cfg(target_os = "linux")]
     window.set_icon(Icon::Buffer(ICON_ARGB.as_ptr(), ICON_ARGB.len() as u32));
 
+    if let Some((x, y)) = app.settings.position {
+        window.set_position(x, y);
+    }
+    if app.settings.fullscreen || args.fullscreen {
+        app.fullscreen = true;
+        set_fullscreen(&window, true, &app.settings);
+    }
+
+    app.open_files(&args.files);
+    if let Some(offset) = args.offset {
+        app.lyric_offset = offset;
+    }
+    if args.theme.is_some() {
+        app.theme_override = args.theme;
+    }
+    if args.autoplay {
+        app.play();
+    }
+
+    #[cfg(any(windows, target_os = "linux"))]
+    accept_file_drop(&window);
+
+    let keymap = Keymap::load();
     let mut prev_mouse_down = false;
+    let mut dragging_timeline = false;
+    let mut dragging_volume = false;
 
-    while window.is_open() && !window.is_key_down(Key::Escape) {
+    while window.is_open() {
+        let theme = app.theme();
         let (new_width, new_height) = window.get_size();
         if new_width != width || new_height != height {
             width = new_width.max(200);
             height = new_height.max(200);
-            buffer.resize(width * height, BG);
+            buffer.resize(width * height, theme.bg);
         }
-        buffer.fill(BG);
+        buffer.fill(theme.bg);
 
         let mouse = window.get_mouse_pos(MouseMode::Clamp).unwrap_or((0.0, 0.0));
         let mouse_down = window.get_mouse_down(MouseButton::Left);
         let clicked = mouse_down && !prev_mouse_down;
         prev_mouse_down = mouse_down;
+        let menu_click = clicked && app.menu.is_some();
+        let clicked = clicked && !menu_click;
+
+        let dropped = take_dropped_files();
+        if !dropped.is_empty() {
+            app.open_files(&dropped);
+        }
+
+        let actions = keymap.pressed(&window);
+        if app.menu.is_some() {
+            menu_keys(&mut app, &window, &actions);
+        } else {
+            for action in actions {
+                run_action(&mut app, &window, action);
+            }
+        }
+
+        app.check_loop();
+
+        if app.song_finished()
+            && app
+                .setlist
+                .as_ref()
+                .is_some_and(|s| s.auto_advance && s.upcoming().is_some())
+        {
+            app.next_song();
+        }
 
         let status_top = 8;
-        let lrc_status = app.lrc_name.as_deref().unwrap_or("No lyrics loaded");
+        let lrc_status = match (&app.lrc_name, app.diagnostics.len()) {
+            (None, _) => "No lyrics loaded".to_string(),
+            (Some(name), 0) => format!("{} [{}]", name, app.lrc_encoding),
+            (Some(name), 1) => format!("{} [{}] (1 issue)", name, app.lrc_encoding),
+            (Some(name), n) => format!("{} [{}] ({} issues)", name, app.lrc_encoding, n),
+        };
         let audio_status = app.audio_name.as_deref().unwrap_or("No audio (optional)");
-        let status_text = format!("LRC: {}  |  Audio: {}", lrc_status, audio_status);
+        let mut status_text = format!("LRC: {}  |  Audio: {}", lrc_status, audio_status);
+        if app.lyric_offset != 0.0 {
+            let offset_ms = (app.lyric_offset * 1000.0).round() as i32;
+            status_text.push_str(&format!("  |  Offset: {:+} ms", offset_ms));
+        }
+        let (look_ahead, pre_roll) = (app.look_ahead(), app.pre_roll());
+        if look_ahead != 0.5 || pre_roll != 1.0 {
+            status_text.push_str(&format!(
+                "  |  Look-ahead: {:.1}s  Pre-roll: {:.1}s",
+                look_ahead, pre_roll
+            ));
+        }
+        match (app.loop_a, app.loop_b) {
+            (Some(a), Some(b)) => status_text.push_str(&format!(
+                "  |  Loop: {}-{}",
+                format_clock(a),
+                format_clock(b)
+            )),
+            (Some(a), None) => status_text.push_str(&format!("  |  Loop from {}", format_clock(a))),
+            _ => {}
+        }
+        if app.speed != 1.0 {
+            status_text.push_str(&format!("  |  Speed: {:.0}%", app.speed * 100.0));
+        }
+        if let Some(setlist) = &app.setlist {
+            status_text.push_str(&format!(
+                "  |  Set: {}/{}{}",
+                setlist.current + 1,
+                setlist.entries.len(),
+                if setlist.auto_advance { " (auto)" } else { "" }
+            ));
+        }
+        if let Some((notice, at)) = &app.notice {
+            if at.elapsed() < Duration::from_secs(4) {
+                status_text.push_str(&format!("  |  {}", notice));
+            }
+        }
         draw_text_centered(
             &mut buffer,
             width,
@@ -73,48 +219,114 @@ fn main() {
             &status_text,
             status_top,
             14.0,
-            GRAY,
+            theme.dim,
             font.as_ref(),
         );
 
-        let elapsed = app.get_elapsed() + 0.5;
-        let idx = find_current_index(&app.lines, elapsed);
+        let now = app.get_elapsed();
+        let lyric_now = app.lyric_time();
+        let elapsed = app.display_time();
+        let pre_roll = app.pre_roll();
+        let idx = find_current_index(&app.lrc.lines, elapsed);
 
-        let (prev, curr, next, countdown, progress) = if app.lines.is_empty() {
-            ("", "Load an LRC file to start", "", 0.0, 0.0)
+        let line_count = app.lrc.lines.len();
+        let (prev, curr, next, countdown, progress, wipe, targets) = if let Some(sync) = &app.sync {
+            (
+                sync.previous(),
+                sync.current(),
+                sync.upcoming(),
+                now,
+                0.0,
+                None,
+                (None, None),
+            )
+        } else if app.lrc.lines.is_empty() {
+            (
+                "",
+                "Load an LRC file to start",
+                "",
+                0.0,
+                0.0,
+                None,
+                (None, None),
+            )
         } else if idx.is_none() {
-            let time_to_first = (app.lines[0].time - elapsed).max(0.0);
-            if time_to_first <= 1.0 {
-                let first = app.lines[0].text.as_str();
-                let second = app.lines.get(1).map(|l| l.text.as_str()).unwrap_or("");
-                ("", first, second, time_to_first, 0.0)
+            let time_to_first = (app.lrc.lines[0].time - elapsed).max(0.0);
+            if time_to_first <= pre_roll {
+                let first = app.lrc.lines[0].text.as_str();
+                let second = app.lrc.lines.get(1).map(|l| l.text.as_str()).unwrap_or("");
+                let wipe = (!app.lrc.lines[0].words.is_empty()).then_some(0.0);
+                let targets = (None, (line_count > 1).then_some(1));
+                ("", first, second, time_to_first, 0.0, wipe, targets)
             } else {
-                let first = app.lines[0].text.as_str();
-                ("", "\u{266A} \u{266A} \u{266A}", first, time_to_first, 0.0)
+                let first = app.lrc.lines[0].text.as_str();
+                (
+                    "",
+                    "\u{266A} \u{266A} \u{266A}",
+                    first,
+                    time_to_first,
+                    0.0,
+                    None,
+                    (None, Some(0)),
+                )
             }
+        } else if let Some(i) = idx.filter(|&i| in_interlude(&app.lrc.lines, i, elapsed, pre_roll))
+        {
+            let line = &app.lrc.lines[i];
+            let next_line = &app.lrc.lines[i + 1];
+            let end = line.end.unwrap_or(line.time);
+            (
+                line.text.as_str(),
+                "\u{266A} \u{266A} \u{266A}",
+                next_line.text.as_str(),
+                (next_line.time - elapsed).max(0.0),
+                ((elapsed - end) / (next_line.time - end)).clamp(0.0, 1.0),
+                None,
+                (Some(i), Some(i + 1)),
+            )
         } else {
             let i = idx.unwrap();
             let prev = if i > 0 {
-                app.lines[i - 1].text.as_str()
+                app.lrc.lines[i - 1].text.as_str()
             } else {
                 ""
             };
-            let curr = app.lines[i].text.as_str();
-            let next = app.lines.get(i + 1).map(|l| l.text.as_str()).unwrap_or("");
+            let curr = app.lrc.lines[i].text.as_str();
+            let next = app
+                .lrc
+                .lines
+                .get(i + 1)
+                .map(|l| l.text.as_str())
+                .unwrap_or("");
             let countdown = app
+                .lrc
                 .lines
                 .get(i + 1)
                 .map(|n| (n.time - elapsed).max(0.0))
                 .unwrap_or(0.0);
             let progress = app
+                .lrc
                 .lines
                 .get(i + 1)
                 .map(|next_line| {
-                    let curr_time = app.lines[i].time;
+                    let curr_time = app.lrc.lines[i].time;
                     ((elapsed - curr_time) / (next_line.time - curr_time)).clamp(0.0, 1.0)
                 })
                 .unwrap_or(0.0);
-            (prev, curr, next, countdown, progress)
+            let line = &app.lrc.lines[i];
+            let wipe = (!line.words.is_empty()).then(|| {
+                let next_time = app.lrc.lines.get(i + 1).map(|n| n.time);
+                line.sung_chars(lyric_now, next_time)
+            });
+            let targets = (i.checked_sub(1), (i + 1 < line_count).then_some(i + 1));
+            (prev, curr, next, countdown, progress, wipe, targets)
+        };
+
+        let gap = app.setlist_gap();
+        let (prev, next) = match &gap {
+            Some(text) if app.start_time.is_none() && prev.is_empty() => (text.as_str(), next),
+            Some(text) if app.start_time.is_some() && next.is_empty() => (prev, text.as_str()),
+            _ => (prev, next),
         };
 
         let main_size = calc_font_size(curr, width, height, font.as_ref());
@@ -123,9 +335,11 @@ fn main() {
         let curr_height = curr_lines.len() as f32 * main_size * 1.1;
 
         let content_top = 40;
-        let bar_area = 90;
+        let bar_area = 100;
         let avail = height - content_top - bar_area;
 
+        let over_prev = targets.0.is_some()
+            && over_text_block(mouse, width, prev, content_top, small_size, font.as_ref());
         draw_text_centered(
             &mut buffer,
             width,
@@ -133,27 +347,81 @@ fn main() {
             prev,
             content_top,
             small_size,
-            GRAY,
+            if over_prev { theme.accent } else { theme.dim },
             font.as_ref(),
         );
 
+        if app.start_time.is_none() && !app.diagnostics.is_empty() {
+            let diag_top = match prev.is_empty() {
+                true => content_top,
+                false => content_top + (small_size * 1.2) as usize,
+            };
+            let shown = app.diagnostics.len().min(4);
+            for (row, diag) in app.diagnostics[..shown].iter().enumerate() {
+                draw_text_centered(
+                    &mut buffer,
+                    width,
+                    height,
+                    &diag.to_string(),
+                    diag_top + row * 18,
+                    14.0,
+                    theme.accent,
+                    font.as_ref(),
+                );
+            }
+            if app.diagnostics.len() > shown {
+                let more = format!("... and {} more", app.diagnostics.len() - shown);
+                draw_text_centered(
+                    &mut buffer,
+                    width,
+                    height,
+                    &more,
+                    diag_top + shown * 18,
+                    14.0,
+                    theme.dim,
+                    font.as_ref(),
+                );
+            }
+        }
+
         let main_top = content_top + avail / 3;
-        let alpha = ((1.0 - progress) * 255.0) as u8;
-        let curr_color = blend(WHITE, BG, alpha.max(120));
-        draw_text_centered(
-            &mut buffer,
-            width,
-            height,
-            curr,
-            main_top,
-            main_size,
-            curr_color,
-            font.as_ref(),
-        );
+        if let Some(sung) = wipe {
+            draw_text_centered_wipe(
+                &mut buffer,
+                width,
+                height,
+                curr,
+                main_top,
+                main_size,
+                theme.highlight,
+                theme.text,
+                sung,
+                font.as_ref(),
+            );
+        } else {
+            let alpha = ((1.0 - progress) * 255.0) as u8;
+            let curr_color = blend(theme.text, theme.bg, alpha.max(120));
+            draw_text_centered(
+                &mut buffer,
+                width,
+                height,
+                curr,
+                main_top,
+                main_size,
+                curr_color,
+                font.as_ref(),
+            );
+        }
 
         let next_top = main_top + curr_height as usize + 30;
         let next_alpha = (progress * 180.0) as u8;
-        let next_color = blend(ACCENT, BG, next_alpha.max(40));
+        let over_next = targets.1.is_some()
+            && over_text_block(mouse, width, next, next_top, small_size, font.as_ref());
+        let next_color = if over_next {
+            theme.text
+        } else {
+            blend(theme.accent, theme.bg, next_alpha.max(40))
+        };
         draw_text_centered(
             &mut buffer,
             width,
@@ -165,13 +433,138 @@ fn main() {
             font.as_ref(),
         );
 
+        if clicked {
+            let target = match (over_prev, over_next) {
+                (true, _) => targets.0,
+                (_, true) => targets.1,
+                _ => None,
+            };
+            if let Some(target) = target {
+                app.jump_to(target);
+            }
+        }
+
+        let duration = app.duration();
+        let timeline_width = (width as f32 * 0.7) as usize;
+        let timeline_left = (width - timeline_width) / 2;
+        let timeline_top = height - 88;
+        let over_timeline = in_rect(
+            mouse,
+            timeline_left as f32,
+            timeline_top as f32 - 6.0,
+            timeline_width as f32,
+            18.0,
+        );
+        if clicked && over_timeline && duration > 0.0 {
+            dragging_timeline = true;
+        }
+        let mut position = now;
+        if dragging_timeline {
+            let frac = ((mouse.0 - timeline_left as f32) / timeline_width as f32).clamp(0.0, 1.0);
+            position = frac * duration;
+            if !mouse_down || (position - now).abs() > 0.5 {
+                app.seek(position);
+            }
+            dragging_timeline = mouse_down;
+        }
+        draw_rect(
+            &mut buffer,
+            width,
+            timeline_left,
+            timeline_top,
+            timeline_width,
+            6,
+            theme.bar_bg,
+        );
+        if duration > 0.0 {
+            let played = (timeline_width as f32 * (position / duration).clamp(0.0, 1.0)) as usize;
+            let color = if over_timeline || dragging_timeline {
+                theme.accent
+            } else {
+                theme.bar_fg
+            };
+            draw_rect(
+                &mut buffer,
+                width,
+                timeline_left,
+                timeline_top,
+                played,
+                6,
+                color,
+            );
+            let marker_x = |t: f32| {
+                timeline_left + (timeline_width as f32 * (t / duration).clamp(0.0, 1.0)) as usize
+            };
+            for t in [app.loop_a, app.loop_b].into_iter().flatten() {
+                draw_rect(
+                    &mut buffer,
+                    width,
+                    marker_x(t).saturating_sub(1),
+                    timeline_top - 4,
+                    2,
+                    14,
+                    theme.highlight,
+                );
+            }
+            if let (Some(a), Some(b)) = (app.loop_a, app.loop_b) {
+                draw_rect(
+                    &mut buffer,
+                    width,
+                    marker_x(a),
+                    timeline_top + 8,
+                    marker_x(b) - marker_x(a),
+                    2,
+                    theme.highlight,
+                );
+            }
+        }
+        let clock_size = 13.0;
+        draw_text(
+            &mut buffer,
+            width,
+            height,
+            &format_clock(position),
+            timeline_left as i32 - 46,
+            timeline_top as i32 - 4,
+            clock_size,
+            theme.dim,
+            font.as_ref(),
+        );
+        draw_text(
+            &mut buffer,
+            width,
+            height,
+            &format_clock(duration),
+            (timeline_left + timeline_width) as i32 + 10,
+            timeline_top as i32 - 4,
+            clock_size,
+            theme.dim,
+            font.as_ref(),
+        );
+
         let bar_width = (width as f32 * 0.5) as usize;
         let bar_left = (width - bar_width) / 2;
         let bar_top = height - 70;
-        draw_rect(&mut buffer, width, bar_left, bar_top, bar_width, 4, BAR_BG);
+        draw_rect(
+            &mut buffer,
+            width,
+            bar_left,
+            bar_top,
+            bar_width,
+            4,
+            theme.bar_bg,
+        );
         let filled = (bar_width as f32 * progress) as usize;
         if filled > 0 {
-            draw_rect(&mut buffer, width, bar_left, bar_top, filled, 4, BAR_FG);
+            draw_rect(
+                &mut buffer,
+                width,
+                bar_left,
+                bar_top,
+                filled,
+                4,
+                theme.bar_fg,
+            );
         }
 
         let time_str = format!("{:.1}s", countdown);
@@ -182,11 +575,71 @@ fn main() {
             &time_str,
             bar_top + 10,
             18.0,
-            GRAY,
+            theme.dim,
+            font.as_ref(),
+        );
+
+        let volume_width = 90;
+        let volume_left = width.saturating_sub(volume_width + 24);
+        let volume_top = bar_top;
+        let over_volume = in_rect(
+            mouse,
+            volume_left as f32,
+            volume_top as f32 - 8.0,
+            volume_width as f32,
+            20.0,
+        );
+        if clicked && over_volume {
+            dragging_volume = true;
+        }
+        if dragging_volume {
+            let frac = (mouse.0 - volume_left as f32) / volume_width as f32;
+            app.set_volume(frac);
+            if !mouse_down {
+                app.settings.save();
+            }
+            dragging_volume = mouse_down;
+        }
+        let volume = if app.muted { 0.0 } else { app.settings.volume };
+        draw_rect(
+            &mut buffer,
+            width,
+            volume_left,
+            volume_top,
+            volume_width,
+            4,
+            theme.bar_bg,
+        );
+        draw_rect(
+            &mut buffer,
+            width,
+            volume_left,
+            volume_top,
+            (volume_width as f32 * volume) as usize,
+            4,
+            if over_volume || dragging_volume {
+                theme.accent
+            } else {
+                theme.bar_fg
+            },
+        );
+        let volume_label = match app.muted {
+            true => "Muted".to_string(),
+            false => format!("Volume {:.0}%", app.settings.volume * 100.0),
+        };
+        draw_text(
+            &mut buffer,
+            width,
+            height,
+            &volume_label,
+            volume_left as i32,
+            volume_top as i32 + 10,
+            12.0,
+            theme.dim,
             font.as_ref(),
         );
 
-        let has_lrc = !app.lines.is_empty();
+        let has_lrc = !app.lrc.lines.is_empty();
         let has_audio = app.audio_path.is_some();
         let play_label = if has_audio {
             "Play"
@@ -197,21 +650,78 @@ fn main() {
         };
 
         let btns = [
-            ("Load LRC", if has_lrc { GREEN } else { ACCENT }),
-            ("Load Audio", if has_audio { GREEN } else { ACCENT }),
-            (play_label, ACCENT),
-            ("Pause", ACCENT),
-            ("Stop", ACCENT),
-            ("Fullscreen", ACCENT),
+            (
+                "Load LRC",
+                if has_lrc { theme.active } else { theme.accent },
+                Action::LoadLyrics,
+            ),
+            (
+                "Load Audio",
+                if has_audio {
+                    theme.active
+                } else {
+                    theme.accent
+                },
+                Action::LoadAudio,
+            ),
+            (
+                "Recent",
+                if app
+                    .menu
+                    .as_ref()
+                    .is_some_and(|m| m.kind == MenuKind::Recent)
+                {
+                    theme.active
+                } else {
+                    theme.accent
+                },
+                Action::Recent,
+            ),
+            (
+                "Setlist",
+                if app.setlist.is_some() {
+                    theme.active
+                } else {
+                    theme.accent
+                },
+                Action::LoadSetlist,
+            ),
+            (
+                "Session",
+                if app.session_path.is_some() {
+                    theme.active
+                } else {
+                    theme.accent
+                },
+                Action::LoadSession,
+            ),
+            (
+                "Save As",
+                if has_lrc { theme.accent } else { theme.dim },
+                Action::SaveAs,
+            ),
+            (
+                "Tap Sync",
+                if app.sync.is_some() {
+                    theme.active
+                } else {
+                    theme.accent
+                },
+                Action::TapSync,
+            ),
+            (play_label, theme.accent, Action::Play),
+            ("Pause", theme.accent, Action::Pause),
+            ("Stop", theme.accent, Action::Stop),
+            ("Fullscreen", theme.accent, Action::Fullscreen),
         ];
-        let btn_width = 90;
         let btn_height = 26;
         let gap = 8;
+        let btn_width = (width.saturating_sub(16 + (btns.len() - 1) * gap) / btns.len()).min(90);
         let total_width = btns.len() * btn_width + (btns.len() - 1) * gap;
-        let start_left = (width - total_width) / 2;
+        let start_left = width.saturating_sub(total_width) / 2;
         let btn_top = height - 38;
 
-        for (idx, (label, color)) in btns.iter().enumerate() {
+        for (idx, (label, color, action)) in btns.iter().enumerate() {
             let btn_left = start_left + idx * (btn_width + gap);
             draw_button(
                 &mut buffer,
@@ -222,6 +732,7 @@ fn main() {
                 btn_height,
                 label,
                 *color,
+                theme.button,
                 font.as_ref(),
             );
             if clicked
@@ -233,174 +744,982 @@ fn main() {
                     btn_height as f32,
                 )
             {
-                match idx {
-                    0 => app.load_lrc(),
-                    1 => app.load_audio(),
-                    2 => app.play(),
-                    3 => app.pause(),
-                    4 => app.stop(),
-                    5 => app.fullscreen = !app.fullscreen,
-                    _ => {}
-                }
-                if idx == 5 {
-                    set_fullscreen(&window, app.fullscreen);
-                }
+                run_action(&mut app, &window, *action);
             }
         }
 
+        if app.menu.is_some() {
+            draw_menu(
+                &mut buffer,
+                width,
+                height,
+                &mut app,
+                theme,
+                mouse,
+                menu_click,
+                font.as_ref(),
+            );
+        }
+
         window.update_with_buffer(&buffer, width, height).unwrap();
     }
+
+    if !app.fullscreen {
+        store_geometry(&mut app.settings, &window);
+    }
+    app.settings.save();
 }
 
-struct LrcLine {
-    time: f32,
-    text: String,
+#[derive(Clone, Copy, PartialEq)]
+enum MenuKind {
+    Recent,
+    Device,
+}
+
+impl MenuKind {
+    fn title(self) -> &'static str {
+        match self {
+            MenuKind::Recent => "Recent",
+            MenuKind::Device => "Output device",
+        }
+    }
+
+    fn empty(self) -> &'static str {
+        match self {
+            MenuKind::Recent => "No recent files",
+            MenuKind::Device => "No output devices found",
+        }
+    }
+}
+
+struct Menu {
+    kind: MenuKind,
+    items: Vec<String>,
+    selected: usize,
 }
 
 struct App {
-    lines: Vec<LrcLine>,
+    lrc: LrcDocument,
+    lrc_path: Option<PathBuf>,
     lrc_name: Option<String>,
+    lrc_encoding: &'static str,
     audio_path: Option<PathBuf>,
     audio_name: Option<String>,
     sink: Option<Arc<Sink>>,
     _stream: Option<OutputStream>,
     start_time: Option<Instant>,
+    start_pos: f32,
     paused_at: Option<f32>,
+    clock: Cell<Option<(f32, Instant)>>,
     fullscreen: bool,
     lyrics_only: bool,
+    lyric_offset: f32,
+    settings: Settings,
+    sync: Option<TapSync>,
+    audio_length: Option<f32>,
+    diagnostics: Vec<Diagnostic>,
+    menu: Option<Menu>,
+    theme_override: Option<String>,
+    setlist: Option<Setlist>,
+    session_path: Option<PathBuf>,
+    notice: Option<(String, Instant)>,
+    session_look_ahead: Option<f32>,
+    session_pre_roll: Option<f32>,
+    start_at: f32,
+    speed: f32,
+    stretch: Option<StretchHandle>,
+    loop_a: Option<f32>,
+    loop_b: Option<f32>,
+    muted: bool,
 }
 
 impl App {
     fn new() -> Self {
         Self {
-            lines: vec![],
+            lrc: LrcDocument::default(),
+            lrc_path: None,
             lrc_name: None,
+            lrc_encoding: "",
             audio_path: None,
             audio_name: None,
             sink: None,
             _stream: None,
             start_time: None,
+            start_pos: 0.0,
             paused_at: None,
+            clock: Cell::new(None),
             fullscreen: false,
             lyrics_only: false,
+            lyric_offset: 0.0,
+            settings: Settings::load(),
+            sync: None,
+            audio_length: None,
+            diagnostics: vec![],
+            menu: None,
+            theme_override: None,
+            setlist: None,
+            session_path: None,
+            notice: None,
+            session_look_ahead: None,
+            session_pre_roll: None,
+            start_at: 0.0,
+            speed: 1.0,
+            stretch: None,
+            loop_a: None,
+            loop_b: None,
+            muted: false,
+        }
+    }
+
+    fn theme(&self) -> Theme {
+        self.settings.theme(
+            self.theme_override
+                .as_deref()
+                .unwrap_or(&self.settings.theme),
+        )
+    }
+
+    fn cycle_theme(&mut self) {
+        let current = self
+            .theme_override
+            .as_deref()
+            .unwrap_or(&self.settings.theme);
+        let next = match current {
+            "dark" => "high-contrast",
+            "high-contrast" | "high_contrast" => "light",
+            _ => "dark",
+        };
+        match &mut self.theme_override {
+            Some(theme) => *theme = next.to_string(),
+            None => {
+                self.settings.theme = next.to_string();
+                self.settings.save();
+            }
         }
     }
 
-    fn load_lrc(&mut self) {
-        if let Some(path) = rfd::FileDialog::new()
+    fn choose_lrc(&mut self) {
+        let dir = self.settings.lyrics_dir.as_deref();
+        if let Some(path) = pick_file("Lyrics", LYRIC_EXTENSIONS, dir) {
+            self.load_lrc(path);
+        }
+    }
+
+    fn load_lrc(&mut self, path: PathBuf) {
+        self.settings.lyrics_dir = path.parent().map(Path::to_path_buf);
+        let audio = self.offer_companion(&path, AUDIO_EXTENSIONS, self.audio_path.as_ref());
+        self.open_lyrics(path);
+        if let Some(audio) = audio {
+            self.open_audio(audio);
+        }
+    }
+
+    fn open_files(&mut self, paths: &[PathBuf]) {
+        let find = |extensions: &[&str]| {
+            paths
+                .iter()
+                .find(|p| lyric_format(p).is_some_and(|e| extensions.contains(&e.as_str())))
+                .cloned()
+        };
+        if let Some(path) = find(&["setlist"]) {
+            self.load_setlist(path);
+            return;
+        }
+        if let Some(path) = find(&["session"]) {
+            self.load_session(path);
+            return;
+        }
+        let (lyrics, audio) = (find(LYRIC_EXTENSIONS), find(AUDIO_EXTENSIONS));
+        if lyrics.is_none() && audio.is_none() {
+            return;
+        }
+        self.stop();
+        self.sync = None;
+        match (lyrics, audio) {
+            (Some(lyrics), Some(audio)) => {
+                self.settings.lyrics_dir = lyrics.parent().map(Path::to_path_buf);
+                self.settings.audio_dir = audio.parent().map(Path::to_path_buf);
+                self.open_lyrics(lyrics);
+                self.open_audio(audio);
+            }
+            (Some(lyrics), None) => self.load_lrc(lyrics),
+            (None, Some(audio)) => self.load_audio(audio),
+            (None, None) => {}
+        }
+    }
+
+    fn offer_companion(
+        &self,
+        path: &Path,
+        extensions: &[&str],
+        current: Option<&PathBuf>,
+    ) -> Option<PathBuf> {
+        let companion = companion(path, extensions).filter(|c| Some(c) != current)?;
+        let accept = match self.settings.pair_files.as_str() {
+            "never" => false,
+            "always" => true,
+            _ => {
+                let name = companion.file_name()?.to_string_lossy();
+                rfd::MessageDialog::new()
+                    .set_title("Load companion file")
+                    .set_description(format!("Also load {}?", name))
+                    .set_buttons(MessageButtons::YesNo)
+                    .show()
+                    == MessageDialogResult::Yes
+            }
+        };
+        accept.then_some(companion)
+    }
+
+    fn open_lyrics(&mut self, path: PathBuf) {
+        let Ok(bytes) = std::fs::read(&path) else {
+            return;
+        };
+        let (content, encoding) = encoding::decode(&bytes);
+        self.lrc = match lyric_format(&path).as_deref() {
+            Some("srt") => parse_srt(&content),
+            Some("vtt") => parse_vtt(&content),
+            _ => parse_lrc(&content),
+        };
+        self.lrc_encoding = encoding;
+        self.lrc_name = self
+            .lrc
+            .display_name()
+            .or_else(|| path.file_name().map(|n| n.to_string_lossy().to_string()));
+        self.diagnostics = self.lrc.diagnostics(self.audio_length);
+        self.settings.add_recent(Some(&path), None);
+        self.settings.save();
+        if self.lrc_path.as_ref() != Some(&path) {
+            self.session_look_ahead = None;
+            self.session_pre_roll = None;
+            self.start_at = 0.0;
+            self.loop_a = None;
+            self.loop_b = None;
+        }
+        self.lrc_path = Some(path);
+        self.lyric_offset = 0.0;
+    }
+
+    fn nudge_offset(&mut self, secs: f32) {
+        self.lyric_offset = ((self.lyric_offset + secs) * 1000.0).round() / 1000.0;
+    }
+
+    fn save_offset(&mut self) {
+        let notice = match self.write_offset() {
+            Ok(()) => "Offset saved".to_string(),
+            Err(reason) => format!("Offset not saved: {}", reason),
+        };
+        self.notice = Some((notice, Instant::now()));
+    }
+
+    fn write_offset(&mut self) -> Result<(), &'static str> {
+        let path = self.lrc_path.clone().ok_or("no lyrics loaded")?;
+        if lyric_format(&path).as_deref() != Some("lrc") {
+            return Err("only LRC files have an offset tag");
+        }
+        if self.lyric_offset == 0.0 {
+            return Err("offset is unchanged");
+        }
+        let bytes = std::fs::read(&path).map_err(|_| "could not read the file")?;
+        let (content, encoding) = encoding::decode(&bytes);
+        let bom = encoding::has_bom(&bytes);
+        if encoding::encode(&content, encoding, bom).as_deref() != Some(&bytes[..]) {
+            return Err("the file would not be written back unchanged");
+        }
+        let offset_ms = self.lrc.offset_ms + (self.lyric_offset * 1000.0).round() as i32;
+        let updated = set_offset_tag(&content, offset_ms);
+        let updated = encoding::encode(&updated, encoding, bom).ok_or("encoding failed")?;
+        std::fs::write(&path, updated).map_err(|_| "could not write the file")?;
+        self.open_lyrics(path);
+        Ok(())
+    }
+
+    fn start_sync(&mut self) {
+        if self.sync.take().is_some() {
+            self.lrc.normalize();
+            self.diagnostics = self.lrc.diagnostics(self.audio_length);
+            return;
+        }
+        let dir = self.settings.lyrics_dir.as_deref();
+        let Some(path) = pick_file("Text", &["txt"], dir) else {
+            return;
+        };
+        let Ok(bytes) = std::fs::read(&path) else {
+            return;
+        };
+        let (content, encoding) = encoding::decode(&bytes);
+        let sync = TapSync::from_text(&content);
+        if sync.is_done() {
+            return;
+        }
+        self.stop();
+        self.lrc = LrcDocument::default();
+        self.lrc_name = path.file_name().map(|n| n.to_string_lossy().to_string());
+        self.lrc_encoding = encoding;
+        self.lrc_path = Some(path);
+        self.diagnostics.clear();
+        self.sync = Some(sync);
+    }
+
+    fn tap(&mut self) {
+        if self.start_time.is_none() || self.paused_at.is_some() {
+            return;
+        }
+        let time = self.get_elapsed();
+        let Some(sync) = &mut self.sync else { return };
+        if let Some(line) = sync.stamp(time) {
+            self.lrc.lines.push(line);
+        }
+        if sync.is_done() {
+            self.sync = None;
+            self.lrc.normalize();
+            self.diagnostics = self.lrc.diagnostics(self.audio_length);
+            self.save_as();
+        }
+    }
+
+    fn untap(&mut self) {
+        if let Some(sync) = &mut self.sync {
+            if sync.undo() {
+                self.lrc.lines.pop();
+            }
+        }
+    }
+
+    fn save_as(&self) {
+        if self.lrc.lines.is_empty() {
+            return;
+        }
+        let stem = self
+            .lrc_path
+            .as_ref()
+            .and_then(|p| p.file_stem())
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "lyrics".to_string());
+        let mut dialog = rfd::FileDialog::new()
             .add_filter("LRC", &["lrc"])
-            .pick_file()
-        {
-            if let Ok(content) = std::fs::read_to_string(&path) {
-                self.lines = parse_lrc(&content);
-                self.lrc_name = path.file_name().map(|n| n.to_string_lossy().to_string());
+            .add_filter("SubRip", &["srt"])
+            .add_filter("WebVTT", &["vtt"])
+            .add_filter("ASS", &["ass"])
+            .set_file_name(format!("{}.lrc", stem));
+        if let Some(dir) = &self.settings.lyrics_dir {
+            dialog = dialog.set_directory(dir);
+        }
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+        let mut format = path
+            .extension()
+            .and_then(|e| Format::from_extension(&e.to_string_lossy()))
+            .unwrap_or(Format::Lrc);
+        let timed = self.lrc.lines.iter().any(|l| !l.words.is_empty());
+        if matches!(format, Format::Lrc) && timed {
+            let keep = rfd::MessageDialog::new()
+                .set_title("Save As")
+                .set_description("Keep word timings? Choose No for plain LRC.")
+                .set_buttons(MessageButtons::YesNo)
+                .show();
+            if keep != MessageDialogResult::Yes {
+                format = Format::PlainLrc;
             }
         }
+        let mut doc = self.lrc.clone();
+        doc.shift(-self.lyric_offset);
+        let _ = std::fs::write(&path, export(&doc, format));
     }
 
-    fn load_audio(&mut self) {
-        if let Some(path) = rfd::FileDialog::new()
-            .add_filter("Audio", &["mp3", "wav", "ogg", "flac"])
-            .pick_file()
-        {
-            self.audio_name = path.file_name().map(|n| n.to_string_lossy().to_string());
-            self.audio_path = Some(path);
+    fn choose_audio(&mut self) {
+        let dir = self.settings.audio_dir.as_deref();
+        if let Some(path) = pick_file("Audio", AUDIO_EXTENSIONS, dir) {
+            self.load_audio(path);
+        }
+    }
+
+    fn load_audio(&mut self, path: PathBuf) {
+        self.settings.audio_dir = path.parent().map(Path::to_path_buf);
+        let lyrics = self.offer_companion(&path, LYRIC_EXTENSIONS, self.lrc_path.as_ref());
+        if let Some(lyrics) = lyrics {
+            self.open_lyrics(lyrics);
+        }
+        self.open_audio(path);
+    }
+
+    fn open_audio(&mut self, path: PathBuf) {
+        self.audio_name = path.file_name().map(|n| n.to_string_lossy().to_string());
+        self.audio_length = File::open(&path)
+            .ok()
+            .and_then(|file| Decoder::new(BufReader::new(file)).ok())
+            .and_then(|source| source.total_duration())
+            .map(|d| d.as_secs_f32());
+        self.settings
+            .add_recent(self.lrc_path.as_deref(), Some(&path));
+        self.settings.save();
+        self.audio_path = Some(path);
+        self.diagnostics = self.lrc.diagnostics(self.audio_length);
+    }
+
+    fn toggle_menu(&mut self, kind: MenuKind) {
+        if self.menu.as_ref().is_some_and(|m| m.kind == kind) {
+            self.menu = None;
+            return;
+        }
+        let (items, selected) = match kind {
+            MenuKind::Recent => (self.settings.recent.iter().map(|r| r.label()).collect(), 0),
+            MenuKind::Device => {
+                let mut items = vec!["System default".to_string()];
+                items.extend(output_device_names());
+                let selected = self
+                    .settings
+                    .output_device
+                    .as_ref()
+                    .and_then(|name| items.iter().position(|item| item == name))
+                    .unwrap_or(0);
+                (items, selected)
+            }
+        };
+        self.menu = Some(Menu {
+            kind,
+            items,
+            selected,
+        });
+    }
+
+    fn choose_menu(&mut self, idx: usize) {
+        let Some(menu) = self.menu.take() else {
+            return;
+        };
+        match menu.kind {
+            MenuKind::Recent => {
+                if let Some(recent) = self.settings.recent.get(idx).cloned() {
+                    self.open_song(recent.lyrics, recent.audio);
+                }
+            }
+            MenuKind::Device if idx < menu.items.len() => {
+                self.settings.output_device = (idx > 0).then(|| menu.items[idx].clone());
+                self.settings.save();
+                self.restart_audio();
+            }
+            MenuKind::Device => {}
+        }
+    }
+
+    fn restart_audio(&mut self) {
+        if self.start_time.is_none() || self.lyrics_only {
+            return;
+        }
+        let (pos, paused) = (self.get_elapsed(), self.paused_at.is_some());
+        self.stop();
+        let start_at = std::mem::replace(&mut self.start_at, pos);
+        self.play();
+        self.start_at = start_at;
+        if paused {
+            self.pause();
+        }
+    }
+
+    fn apply_volume(&self) {
+        if let Some(sink) = &self.sink {
+            sink.set_volume(if self.muted {
+                0.0
+            } else {
+                self.settings.volume
+            });
+        }
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        self.settings.volume = ((volume * 100.0).round() / 100.0).clamp(0.0, 1.0);
+        self.muted = false;
+        self.apply_volume();
+    }
+
+    fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+        self.apply_volume();
+    }
+
+    fn open_stream(&self) -> Option<OutputStream> {
+        let device = self.settings.output_device.as_ref().and_then(|name| {
+            cpal::default_host()
+                .output_devices()
+                .ok()?
+                .find(|d| d.name().ok().as_ref() == Some(name))
+        });
+        match device {
+            Some(device) => OutputStreamBuilder::from_device(device)
+                .and_then(|builder| builder.open_stream_or_fallback())
+                .ok(),
+            None => OutputStreamBuilder::open_default_stream().ok(),
+        }
+    }
+
+    fn open_song(&mut self, lyrics: Option<PathBuf>, audio: Option<PathBuf>) {
+        self.stop();
+        self.sync = None;
+        self.audio_path = None;
+        self.audio_name = None;
+        self.audio_length = None;
+        match lyrics {
+            Some(path) => self.open_lyrics(path),
+            None => {
+                self.lrc = LrcDocument::default();
+                self.lrc_path = None;
+                self.lrc_name = None;
+                self.lyric_offset = 0.0;
+                self.session_look_ahead = None;
+                self.session_pre_roll = None;
+            }
+        }
+        if let Some(path) = audio {
+            self.open_audio(path);
+        }
+        self.diagnostics = self.lrc.diagnostics(self.audio_length);
+    }
+
+    fn choose_session(&mut self) {
+        let dir = self.settings.lyrics_dir.as_deref();
+        if let Some(path) = pick_file("Session", &["session"], dir) {
+            self.load_session(path);
+        }
+    }
+
+    fn load_session(&mut self, path: PathBuf) {
+        let Ok(bytes) = std::fs::read(&path) else {
+            return;
+        };
+        let session = Session::parse(&encoding::decode(&bytes).0, &path);
+        self.open_song(session.lyrics, session.audio);
+        self.lyric_offset = session.offset;
+        self.start_at = session.position;
+        self.theme_override = session.theme;
+        self.session_look_ahead = session.look_ahead;
+        self.session_pre_roll = session.pre_roll;
+        self.session_path = Some(path);
+    }
+
+    fn save_session(&mut self) {
+        if self.lrc_path.is_none() && self.audio_path.is_none() {
+            return;
+        }
+        let mut dialog = rfd::FileDialog::new().add_filter("Session", &["session"]);
+        dialog = match (&self.session_path, &self.lrc_path) {
+            (Some(path), _) => dialog
+                .set_directory(path.parent().unwrap_or(Path::new("")))
+                .set_file_name(path.file_name().unwrap_or_default().to_string_lossy()),
+            (None, Some(lyrics)) => dialog
+                .set_directory(lyrics.parent().unwrap_or(Path::new("")))
+                .set_file_name(format!(
+                    "{}.session",
+                    lyrics.file_stem().unwrap_or_default().to_string_lossy()
+                )),
+            (None, None) => dialog,
+        };
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+        let position = match self.start_time {
+            Some(_) => self.get_elapsed(),
+            None => self.start_at,
+        };
+        let session = Session {
+            lyrics: self.lrc_path.clone(),
+            audio: self.audio_path.clone(),
+            offset: self.lyric_offset,
+            look_ahead: Some(self.look_ahead()).filter(|v| *v != self.saved_look_ahead()),
+            pre_roll: Some(self.pre_roll()).filter(|v| *v != self.saved_pre_roll()),
+            theme: self.theme_override.clone(),
+            position,
+        };
+        let dir = path.parent().unwrap_or(Path::new(""));
+        if std::fs::write(&path, session.to_string_in(dir)).is_ok() {
+            self.session_path = Some(path);
+        }
+    }
+
+    fn choose_setlist(&mut self) {
+        let dir = self.settings.lyrics_dir.as_deref();
+        if let Some(path) = pick_file("Setlist", &["setlist"], dir) {
+            self.load_setlist(path);
+        }
+    }
+
+    fn load_setlist(&mut self, path: PathBuf) {
+        let Ok(bytes) = std::fs::read(&path) else {
+            return;
+        };
+        let setlist = Setlist::parse(&encoding::decode(&bytes).0, &path);
+        if setlist.entries.is_empty() {
+            return;
+        }
+        self.setlist = Some(setlist);
+        self.open_setlist_entry(0);
+    }
+
+    fn save_setlist(&mut self) {
+        let lyric_offset = self.lyric_offset;
+        let Some(setlist) = &mut self.setlist else {
+            return;
+        };
+        if let Some(entry) = setlist.entries.get_mut(setlist.current) {
+            entry.offset = lyric_offset;
+        }
+        let mut dialog = rfd::FileDialog::new().add_filter("Setlist", &["setlist"]);
+        dialog = match &setlist.path {
+            Some(path) => dialog
+                .set_directory(path.parent().unwrap_or(Path::new("")))
+                .set_file_name(path.file_name().unwrap_or_default().to_string_lossy()),
+            None => dialog.set_file_name("show.setlist"),
+        };
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+        let dir = path.parent().unwrap_or(Path::new(""));
+        if std::fs::write(&path, setlist.to_string_in(dir)).is_ok() {
+            setlist.path = Some(path);
+        }
+    }
+
+    fn add_to_setlist(&mut self) {
+        let Some(lyrics) = self.lrc_path.clone() else {
+            return;
+        };
+        let setlist = self.setlist.get_or_insert_with(Setlist::default);
+        setlist.entries.push(SetlistEntry {
+            lyrics,
+            audio: self.audio_path.clone(),
+            offset: self.lyric_offset,
+            ..SetlistEntry::default()
+        });
+        setlist.current = setlist.entries.len() - 1;
+    }
+
+    fn open_setlist_entry(&mut self, idx: usize) {
+        let lyric_offset = self.lyric_offset;
+        let Some(setlist) = &mut self.setlist else {
+            return;
+        };
+        if let Some(entry) = setlist.entries.get_mut(setlist.current) {
+            if self.lrc_path.as_ref() == Some(&entry.lyrics) {
+                entry.offset = lyric_offset;
+            }
+        }
+        let Some(entry) = setlist.entries.get(idx).cloned() else {
+            return;
+        };
+        setlist.current = idx;
+        self.open_song(Some(entry.lyrics), entry.audio);
+        self.lyric_offset = entry.offset;
+    }
+
+    fn next_song(&mut self) {
+        if let Some(setlist) = &self.setlist {
+            self.open_setlist_entry(setlist.current + 1);
+        }
+    }
+
+    fn prev_song(&mut self) {
+        if let Some(setlist) = &self.setlist {
+            self.open_setlist_entry(setlist.current.saturating_sub(1));
+        }
+    }
+
+    fn toggle_auto_advance(&mut self) {
+        if let Some(setlist) = &mut self.setlist {
+            setlist.auto_advance = !setlist.auto_advance;
+        }
+    }
+
+    fn song_finished(&self) -> bool {
+        if self.start_time.is_none() || self.paused_at.is_some() {
+            return false;
+        }
+        match &self.sink {
+            Some(sink) if !self.lyrics_only => sink.empty(),
+            _ => self.display_time() >= self.duration(),
+        }
+    }
+
+    fn setlist_gap(&self) -> Option<String> {
+        let setlist = self.setlist.as_ref()?;
+        match self.start_time {
+            None => Some(format!("Up next: {}", setlist.current()?.label())),
+            Some(_) => Some(format!("Next song: {}", setlist.upcoming()?.label())),
         }
     }
 
     fn play(&mut self) {
         if let Some(paused) = self.paused_at.take() {
             if self.lyrics_only {
-                self.start_time = Some(Instant::now() - std::time::Duration::from_secs_f32(paused));
+                self.rebase(paused);
                 return;
             }
             if let Some(sink) = &self.sink {
                 sink.play();
-                self.start_time = Some(Instant::now() - std::time::Duration::from_secs_f32(paused));
+                self.clock.set(None);
+                self.rebase(paused);
             }
             return;
         }
 
-        if self.audio_path.is_none() && !self.lines.is_empty() {
+        if self.audio_path.is_none() && (!self.lrc.lines.is_empty() || self.sync.is_some()) {
             self.lyrics_only = true;
-            self.start_time = Some(Instant::now());
+            self.rebase(0.0);
+            self.seek(self.start_at);
             return;
         }
 
         let Some(path) = &self.audio_path else { return };
         let Ok(file) = File::open(path) else { return };
-        let Ok(stream) = OutputStreamBuilder::open_default_stream() else {
+        let Some(stream) = self.open_stream() else {
+            return;
+        };
+        let Ok(source) = Decoder::new(BufReader::new(file)) else {
+            return;
+        };
+        let stretch = StretchHandle::new(self.speed);
+        let sink = Sink::connect_new(stream.mixer());
+        sink.append(TimeStretch::new(source, stretch.clone()));
+        sink.play();
+        self.sink = Some(Arc::new(sink));
+        self.stretch = Some(stretch);
+        self.apply_volume();
+        self._stream = Some(stream);
+        self.rebase(0.0);
+        self.lyrics_only = false;
+        self.seek(self.start_at);
+    }
+
+    fn play_pause(&mut self) {
+        if self.start_time.is_some() && self.paused_at.is_none() {
+            self.pause();
+        } else {
+            self.play();
+        }
+    }
+
+    fn pause(&mut self) {
+        if self.lyrics_only {
+            self.paused_at = Some(self.get_elapsed());
+            return;
+        }
+        if let Some(sink) = &self.sink {
+            sink.pause();
+            self.paused_at = Some(self.get_elapsed());
+        }
+    }
+
+    fn stop(&mut self) {
+        if let Some(sink) = &self.sink {
+            sink.stop();
+        }
+        self.sink = None;
+        self.stretch = None;
+        self._stream = None;
+        self.start_time = None;
+        self.paused_at = None;
+        self.clock.set(None);
+        self.lyrics_only = false;
+    }
+
+    fn duration(&self) -> f32 {
+        if let Some(length) = self.audio_length.or(self.lrc.length) {
+            return length;
+        }
+        self.lrc
+            .lines
+            .last()
+            .map(|l| l.end.unwrap_or(l.time + 5.0))
+            .unwrap_or(0.0)
+    }
+
+    fn seek(&mut self, pos: f32) {
+        let pos = pos.clamp(0.0, self.duration());
+        if self.start_time.is_none() {
+            self.play();
+            if self.start_time.is_none() {
+                return;
+            }
+        }
+        if !self.lyrics_only {
+            let Some(sink) = &self.sink else { return };
+            if sink.try_seek(Duration::from_secs_f32(pos)).is_err() {
+                return;
+            }
+            self.clock.set(None);
+        }
+        if self.paused_at.is_some() {
+            self.paused_at = Some(pos);
+        } else {
+            self.rebase(pos);
+        }
+    }
+
+    fn jump_line(&mut self, delta: i32) {
+        if self.lrc.lines.is_empty() {
+            return;
+        }
+        let last = self.lrc.lines.len() as i32 - 1;
+        match find_current_index(&self.lrc.lines, self.display_time()) {
+            Some(i) => self.jump_to((i as i32 + delta).clamp(0, last) as usize),
+            None if delta > 0 => self.jump_to(0),
+            None => self.seek(0.0),
+        }
+    }
+
+    fn jump_to(&mut self, idx: usize) {
+        let Some(line) = self.lrc.lines.get(idx) else {
+            return;
+        };
+        let lead = self.display_time() - self.get_elapsed();
+        self.seek((line.time - lead + SEEK_MARGIN).max(0.0));
+    }
+
+    fn lyric_time(&self) -> f32 {
+        self.get_elapsed() + self.lyric_offset
+    }
+
+    fn display_time(&self) -> f32 {
+        self.lyric_time() + self.look_ahead()
+    }
+
+    fn song_settings(&self) -> Option<&SongSettings> {
+        self.settings.song(self.lrc_path.as_deref()?)
+    }
+
+    fn look_ahead(&self) -> f32 {
+        self.session_look_ahead
+            .unwrap_or_else(|| self.saved_look_ahead())
+    }
+
+    fn saved_look_ahead(&self) -> f32 {
+        self.song_settings()
+            .and_then(|s| s.look_ahead)
+            .unwrap_or(self.settings.look_ahead)
+    }
+
+    fn pre_roll(&self) -> f32 {
+        self.session_pre_roll
+            .unwrap_or_else(|| self.saved_pre_roll())
+    }
+
+    fn saved_pre_roll(&self) -> f32 {
+        self.song_settings()
+            .and_then(|s| s.pre_roll)
+            .unwrap_or(self.settings.pre_roll)
+    }
+
+    fn adjust_look_ahead(&mut self, delta: f32) {
+        let value = (((self.look_ahead() + delta) * 10.0).round() / 10.0).clamp(0.0, 10.0);
+        match &self.lrc_path {
+            _ if self.session_look_ahead.is_some() => self.session_look_ahead = Some(value),
+            Some(path) => self.settings.song_mut(path).look_ahead = Some(value),
+            None => self.settings.look_ahead = value,
+        }
+        self.settings.save();
+    }
+
+    fn adjust_pre_roll(&mut self, delta: f32) {
+        let value = (((self.pre_roll() + delta) * 10.0).round() / 10.0).clamp(0.0, 10.0);
+        match &self.lrc_path {
+            _ if self.session_pre_roll.is_some() => self.session_pre_roll = Some(value),
+            Some(path) => self.settings.song_mut(path).pre_roll = Some(value),
+            None => self.settings.pre_roll = value,
+        }
+        self.settings.save();
+    }
+
+    fn set_loop(&mut self, a: Option<f32>, b: Option<f32>) {
+        let (a, b) = match (a, b) {
+            (Some(a), Some(b)) if b < a => (Some(b), Some(a)),
+            _ => (a, b),
+        };
+        let b = b.map(|b| b.min(self.duration() - LOOP_TAIL));
+        (self.loop_a, self.loop_b) = match (a, b) {
+            (Some(a), Some(b)) if b - a < 0.1 => (Some(a), None),
+            _ => (a, b),
+        };
+    }
+
+    fn toggle_loop(&mut self) {
+        let now = self.get_elapsed();
+        match (self.loop_a, self.loop_b) {
+            (None, _) => self.set_loop(Some(now), None),
+            (Some(a), None) => self.set_loop(Some(a), Some(now)),
+            _ => self.set_loop(None, None),
+        }
+    }
+
+    fn loop_line(&mut self) {
+        let lines = &self.lrc.lines;
+        let lead = self.display_time() - self.get_elapsed();
+        let media = |idx: usize| lines.get(idx).map(|l| (l.time - lead).max(0.0));
+        let Some(current) = find_current_index(lines, self.display_time()) else {
             return;
         };
-        let Ok(source) = Decoder::new(BufReader::new(file)) else {
-            return;
+        let (a, b) = match (self.loop_a, self.loop_b) {
+            (Some(a), Some(b)) => {
+                let next = find_current_index(lines, b + lead + 0.01).map_or(0, |i| i + 1);
+                (Some(a), media(next).or(Some(self.duration())))
+            }
+            _ => (media(current), media(current + 1).or(Some(self.duration()))),
         };
-        let sink = Sink::connect_new(&stream.mixer());
-        sink.append(source);
-        sink.play();
-        self.sink = Some(Arc::new(sink));
-        self._stream = Some(stream);
-        self.start_time = Some(Instant::now());
-        self.lyrics_only = false;
+        self.set_loop(a, b);
     }
 
-    fn pause(&mut self) {
-        if self.lyrics_only {
-            self.paused_at = Some(self.get_elapsed());
+    fn check_loop(&mut self) {
+        let (Some(a), Some(b)) = (self.loop_a, self.loop_b) else {
+            return;
+        };
+        let end = b.min(self.duration() - LOOP_TAIL);
+        if end - a < 0.1 {
             return;
         }
-        if let Some(sink) = &self.sink {
-            sink.pause();
-            self.paused_at = Some(self.get_elapsed());
+        if self.start_time.is_some() && self.paused_at.is_none() && self.get_elapsed() >= end {
+            self.seek(a);
         }
     }
 
-    fn stop(&mut self) {
-        if let Some(sink) = &self.sink {
-            sink.stop();
+    fn set_speed(&mut self, speed: f32) {
+        let elapsed = self.get_elapsed();
+        self.speed = ((speed * 20.0).round() / 20.0).clamp(0.5, 1.5);
+        if let Some(stretch) = &self.stretch {
+            stretch.set_speed(self.speed);
         }
-        self.sink = None;
-        self._stream = None;
-        self.start_time = None;
-        self.paused_at = None;
-        self.lyrics_only = false;
+        self.clock.set(None);
+        if self.start_time.is_some() && self.paused_at.is_none() {
+            self.rebase(elapsed);
+        }
+    }
+
+    fn rebase(&mut self, pos: f32) {
+        self.start_time = Some(Instant::now());
+        self.start_pos = pos;
     }
 
     fn get_elapsed(&self) -> f32 {
-        self.paused_at.unwrap_or_else(|| {
-            self.start_time
-                .map(|t| t.elapsed().as_secs_f32())
-                .unwrap_or(0.0)
-        })
-    }
-}
-
-fn parse_lrc(content: &str) -> Vec<LrcLine> {
-    content
-        .lines()
-        .filter_map(|line| {
-            let line = line.trim();
-            if !line.starts_with('[') {
-                return None;
-            }
-            let end = line.find(']')?;
-            let ts = &line[1..end];
-            let parts: Vec<&str> = ts.split(':').collect();
-            if parts.len() != 2 {
-                return None;
-            }
-            let mins: f32 = parts[0].parse().ok()?;
-            let secs: f32 = parts[1].parse().ok()?;
-            let text = line[end + 1..].trim().to_string();
-            if text.is_empty() {
-                return None;
-            }
-            Some(LrcLine {
-                time: mins * 60.0 + secs,
-                text,
-            })
-        })
-        .collect()
+        if let Some(paused) = self.paused_at {
+            return paused;
+        }
+        if let (false, Some(stretch)) = (self.lyrics_only, &self.stretch) {
+            return self.sink_position(stretch);
+        }
+        self.start_time
+            .map(|t| self.start_pos + t.elapsed().as_secs_f32() * self.speed)
+            .unwrap_or(0.0)
+    }
+
+    fn sink_position(&self, stretch: &StretchHandle) -> f32 {
+        let raw = stretch.position();
+        let now = Instant::now();
+        let (pos, at) = match self.clock.get() {
+            Some((pos, at)) if pos == raw => (pos, at),
+            _ => {
+                self.clock.set(Some((raw, now)));
+                (raw, now)
+            }
+        };
+        pos + (now - at).as_secs_f32().min(0.25) * self.speed
+    }
 }
 
 fn find_current_index(lines: &[LrcLine], time: f32) -> Option<usize> {
@@ -415,6 +1734,291 @@ fn find_current_index(lines: &[LrcLine], time: f32) -> Option<usize> {
         .map(|(idx, _)| idx)
 }
 
+fn run_action(app: &mut App, window: &Window, action: Action) {
+    match action {
+        Action::LoadLyrics => app.choose_lrc(),
+        Action::LoadAudio => app.choose_audio(),
+        Action::Recent => app.toggle_menu(MenuKind::Recent),
+        Action::OutputDevice => app.toggle_menu(MenuKind::Device),
+        Action::VolumeUp => {
+            app.set_volume(app.settings.volume + 0.05);
+            app.settings.save();
+        }
+        Action::VolumeDown => {
+            app.set_volume(app.settings.volume - 0.05);
+            app.settings.save();
+        }
+        Action::Mute => app.toggle_mute(),
+        Action::LoadSession => app.choose_session(),
+        Action::SaveSession => app.save_session(),
+        Action::LoadSetlist => app.choose_setlist(),
+        Action::SaveSetlist => app.save_setlist(),
+        Action::AddToSetlist => app.add_to_setlist(),
+        Action::NextSong => app.next_song(),
+        Action::PrevSong => app.prev_song(),
+        Action::AutoAdvance => app.toggle_auto_advance(),
+        Action::SaveAs => app.save_as(),
+        Action::TapSync => app.start_sync(),
+        Action::Play => app.play(),
+        Action::Pause => app.pause(),
+        Action::PlayPause => app.play_pause(),
+        Action::Stop => app.stop(),
+        Action::Fullscreen => toggle_fullscreen(app, window, !app.fullscreen),
+        Action::ExitFullscreen => toggle_fullscreen(app, window, false),
+        Action::CycleTheme => app.cycle_theme(),
+        Action::PrevLine => app.jump_line(-1),
+        Action::NextLine => app.jump_line(1),
+        Action::RestartLine => app.jump_line(0),
+        Action::OffsetUp => app.nudge_offset(0.1),
+        Action::OffsetDown => app.nudge_offset(-0.1),
+        Action::OffsetUpFine => app.nudge_offset(0.05),
+        Action::OffsetDownFine => app.nudge_offset(-0.05),
+        Action::SaveOffset => app.save_offset(),
+        Action::LoopA => app.set_loop(Some(app.get_elapsed()), app.loop_b),
+        Action::LoopB => app.set_loop(app.loop_a.or(Some(0.0)), Some(app.get_elapsed())),
+        Action::LoopToggle => app.toggle_loop(),
+        Action::LoopLine => app.loop_line(),
+        Action::LoopClear => app.set_loop(None, None),
+        Action::SpeedUp => app.set_speed(app.speed + 0.05),
+        Action::SpeedDown => app.set_speed(app.speed - 0.05),
+        Action::SpeedReset => app.set_speed(1.0),
+        Action::LookAheadUp => app.adjust_look_ahead(0.1),
+        Action::LookAheadDown => app.adjust_look_ahead(-0.1),
+        Action::PreRollUp => app.adjust_pre_roll(0.5),
+        Action::PreRollDown => app.adjust_pre_roll(-0.5),
+        Action::Tap => app.tap(),
+        Action::Untap => app.untap(),
+    }
+}
+
+fn menu_keys(app: &mut App, window: &Window, actions: &[Action]) {
+    const DIGITS: [Key; 9] = [
+        Key::Key1,
+        Key::Key2,
+        Key::Key3,
+        Key::Key4,
+        Key::Key5,
+        Key::Key6,
+        Key::Key7,
+        Key::Key8,
+        Key::Key9,
+    ];
+    let Some(menu) = &mut app.menu else {
+        return;
+    };
+    let last = menu.items.len().saturating_sub(1);
+    let pressed = |key| window.is_key_pressed(key, KeyRepeat::Yes);
+    if actions.contains(&Action::Recent) {
+        app.toggle_menu(MenuKind::Recent);
+    } else if actions.contains(&Action::OutputDevice) {
+        app.toggle_menu(MenuKind::Device);
+    } else if pressed(Key::Escape) {
+        app.menu = None;
+    } else if pressed(Key::Up) {
+        menu.selected = menu.selected.saturating_sub(1);
+    } else if pressed(Key::Down) {
+        menu.selected = (menu.selected + 1).min(last);
+    } else if pressed(Key::Enter) || pressed(Key::NumPadEnter) {
+        let selected = menu.selected;
+        app.choose_menu(selected);
+    } else if let Some(idx) = DIGITS.iter().position(|key| pressed(*key)) {
+        app.choose_menu(idx);
+    }
+}
+
+fn draw_menu(
+    buf: &mut [u32],
+    width: usize,
+    height: usize,
+    app: &mut App,
+    theme: Theme,
+    mouse: (f32, f32),
+    clicked: bool,
+    font: Option<&Font>,
+) {
+    let Some(menu) = &app.menu else {
+        return;
+    };
+    let row_height = 26;
+    let panel_width = width.saturating_sub(40).min(720);
+    let rows = ((height.saturating_sub(140)) / row_height).max(1);
+    let shown = menu.items.len().clamp(1, rows);
+    let panel_height = 44 + shown * row_height;
+    let left = (width - panel_width) / 2;
+    let top = height.saturating_sub(panel_height) / 2;
+    draw_rect(
+        buf,
+        width,
+        left,
+        top,
+        panel_width,
+        panel_height,
+        theme.button,
+    );
+    draw_text(
+        buf,
+        width,
+        height,
+        &format!(
+            "{} - Enter or 1-9 to choose, Esc to close",
+            menu.kind.title()
+        ),
+        left as i32 + 14,
+        top as i32 + 12,
+        14.0,
+        theme.dim,
+        font,
+    );
+    if menu.items.is_empty() {
+        draw_text(
+            buf,
+            width,
+            height,
+            menu.kind.empty(),
+            left as i32 + 14,
+            (top + 44) as i32,
+            16.0,
+            theme.accent,
+            font,
+        );
+    }
+    let first = (menu.selected + 1).saturating_sub(shown);
+    let mut chosen = None;
+    for (row, item) in menu.items.iter().enumerate().skip(first).take(shown) {
+        let row_top = top + 38 + (row - first) * row_height;
+        let hover = in_rect(
+            mouse,
+            left as f32,
+            row_top as f32,
+            panel_width as f32,
+            row_height as f32,
+        );
+        if row == menu.selected || hover {
+            draw_rect(
+                buf,
+                width,
+                left,
+                row_top,
+                panel_width,
+                row_height,
+                theme.bar_bg,
+            );
+        }
+        let label = match row {
+            0..=8 => format!("{}  {}", row + 1, item),
+            _ => format!("   {}", item),
+        };
+        draw_text(
+            buf,
+            width,
+            height,
+            &label,
+            left as i32 + 14,
+            row_top as i32 + 5,
+            16.0,
+            if row == menu.selected {
+                theme.text
+            } else {
+                theme.accent
+            },
+            font,
+        );
+        if clicked && hover {
+            chosen = Some(row);
+        }
+    }
+    let inside = in_rect(
+        mouse,
+        left as f32,
+        top as f32,
+        panel_width as f32,
+        panel_height as f32,
+    );
+    match chosen {
+        Some(idx) => app.choose_menu(idx),
+        None if clicked && !inside => app.menu = None,
+        None => {}
+    }
+}
+
+fn toggle_fullscreen(app: &mut App, window: &Window, fullscreen: bool) {
+    if app.fullscreen == fullscreen {
+        return;
+    }
+    if fullscreen {
+        store_geometry(&mut app.settings, window);
+    }
+    app.fullscreen = fullscreen;
+    app.settings.fullscreen = fullscreen;
+    set_fullscreen(window, fullscreen, &app.settings);
+    app.settings.save();
+}
+
+fn store_geometry(settings: &mut Settings, window: &Window) {
+    let (width, height) = window.get_size();
+    settings.width = width.max(200);
+    settings.height = height.max(200);
+    settings.position = Some(window.get_position());
+}
+
+fn output_device_names() -> Vec<String> {
+    cpal::default_host()
+        .output_devices()
+        .map(|devices| devices.filter_map(|d| d.name().ok()).collect())
+        .unwrap_or_default()
+}
+
+fn pick_file(name: &str, extensions: &[&str], dir: Option<&Path>) -> Option<PathBuf> {
+    let mut dialog = rfd::FileDialog::new().add_filter(name, extensions);
+    if let Some(dir) = dir {
+        dialog = dialog.set_directory(dir);
+    }
+    dialog.pick_file()
+}
+
+fn companion(path: &Path, extensions: &[&str]) -> Option<PathBuf> {
+    extensions
+        .iter()
+        .flat_map(|ext| [ext.to_string(), ext.to_ascii_uppercase()])
+        .map(|ext| path.with_extension(ext))
+        .find(|candidate| candidate.is_file())
+}
+
+fn lyric_format(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+}
+
+fn format_clock(secs: f32) -> String {
+    let secs = secs.max(0.0) as u32;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+fn over_text_block(
+    pos: (f32, f32),
+    buf_width: usize,
+    text: &str,
+    top: usize,
+    size: f32,
+    font: Option<&Font>,
+) -> bool {
+    if text.is_empty() {
+        return false;
+    }
+    let lines = wrap_text(text, buf_width as f32 * 0.95, size, font);
+    let line_height = size * 1.1;
+    let total = lines.len() as f32 * line_height;
+    let start = top as f32 - (total - line_height) / 2.0;
+    in_rect(pos, 0.0, start, buf_width as f32, total)
+}
+
+fn in_interlude(lines: &[LrcLine], idx: usize, time: f32, pre_roll: f32) -> bool {
+    let (Some(end), Some(next)) = (lines[idx].end, lines.get(idx + 1)) else {
+        return false;
+    };
+    time >= end && next.time - time > pre_roll
+}
+
 fn calc_font_size(text: &str, width: usize, height: usize, font: Option<&Font>) -> f32 {
     let Some(font) = font else { return 60.0 };
     let max_width = width as f32 * 0.95;
@@ -453,7 +2057,7 @@ fn draw_rect(
 }
 
 fn blend(fg: u32, bg: u32, alpha: u8) -> u32 {
-    let mix = |f: u32, b: u32| ((f * alpha as u32 + b * (255 - alpha as u32)) / 255) as u32;
+    let mix = |f: u32, b: u32| (f * alpha as u32 + b * (255 - alpha as u32)) / 255;
     let red = mix((fg >> 16) & 0xff, (bg >> 16) & 0xff);
     let green = mix((fg >> 8) & 0xff, (bg >> 8) & 0xff);
     let blue = mix(fg & 0xff, bg & 0xff);
@@ -474,6 +2078,34 @@ fn draw_text(
     size: f32,
     color: u32,
     font: Option<&Font>,
+) {
+    draw_text_wipe(
+        buf,
+        buf_width,
+        buf_height,
+        text,
+        left,
+        top,
+        size,
+        color,
+        color,
+        f32::INFINITY,
+        font,
+    );
+}
+
+fn draw_text_wipe(
+    buf: &mut [u32],
+    buf_width: usize,
+    buf_height: usize,
+    text: &str,
+    left: i32,
+    top: i32,
+    size: f32,
+    sung: u32,
+    unsung: u32,
+    wipe_x: f32,
+    font: Option<&Font>,
 ) {
     let Some(font) = font else { return };
     let scale = Scale::uniform(size);
@@ -485,6 +2117,7 @@ fn draw_text(
                 let px = (bb.min.x + gx as i32) as usize;
                 let py = (bb.min.y + gy as i32) as usize;
                 if px < buf_width && py < buf_height {
+                    let color = if (px as f32) < wipe_x { sung } else { unsung };
                     buf[py * buf_width + px] =
                         blend(color, buf[py * buf_width + px], (val * 255.0) as u8);
                 }
@@ -504,6 +2137,21 @@ fn text_width(text: &str, size: f32, font: Option<&Font>) -> f32 {
         .unwrap_or(0.0)
 }
 
+fn char_offset_x(text: &str, chars: f32, size: f32, font: Option<&Font>) -> f32 {
+    let Some(font) = font else {
+        return chars * size * 0.5;
+    };
+    if chars <= 0.0 {
+        return f32::NEG_INFINITY;
+    }
+    let scale = Scale::uniform(size);
+    let glyphs: Vec<_> = font.layout(text, scale, point(0.0, 0.0)).collect();
+    let Some(glyph) = glyphs.get(chars as usize) else {
+        return f32::INFINITY;
+    };
+    glyph.position().x + chars.fract() * glyph.unpositioned().h_metrics().advance_width
+}
+
 fn wrap_text(text: &str, max_width: f32, size: f32, font: Option<&Font>) -> Vec<String> {
     let words: Vec<&str> = text.split_whitespace().collect();
     if words.is_empty() {
@@ -545,6 +2193,33 @@ fn draw_text_centered(
     color: u32,
     font: Option<&Font>,
 ) {
+    draw_text_centered_wipe(
+        buf,
+        buf_width,
+        buf_height,
+        text,
+        top,
+        size,
+        color,
+        color,
+        f32::INFINITY,
+        font,
+    );
+}
+
+fn draw_text_centered_wipe(
+    buf: &mut [u32],
+    buf_width: usize,
+    buf_height: usize,
+    text: &str,
+    top: usize,
+    size: f32,
+    sung: u32,
+    unsung: u32,
+    sung_chars: f32,
+    font: Option<&Font>,
+) {
+    let mut consumed = 0.0;
     let max_width = buf_width as f32 * 0.95;
     let lines = wrap_text(text, max_width, size, font);
     let line_height = size * 1.1;
@@ -554,7 +2229,9 @@ fn draw_text_centered(
         let width = text_width(line, size, font);
         let left = ((buf_width as f32 - width) / 2.0).max(0.0) as i32;
         let line_top = start + idx as f32 * line_height;
-        draw_text(
+        let wipe_x = left as f32 + char_offset_x(line, sung_chars - consumed, size, font);
+        consumed += line.chars().count() as f32 + 1.0;
+        draw_text_wipe(
             buf,
             buf_width,
             buf_height,
@@ -562,7 +2239,9 @@ fn draw_text_centered(
             left,
             line_top as i32,
             size,
-            color,
+            sung,
+            unsung,
+            wipe_x,
             font,
         );
     }
@@ -577,9 +2256,10 @@ fn draw_button(
     height: usize,
     label: &str,
     color: u32,
+    bg: u32,
     font: Option<&Font>,
 ) {
-    draw_rect(buf, buf_width, left, top, width, height, BTN_BG);
+    draw_rect(buf, buf_width, left, top, width, height, bg);
     let size = 13.0;
     let tw = text_width(label, size, font);
     let tx = left as i32 + ((width as f32 - tw) / 2.0) as i32;
@@ -597,6 +2277,337 @@ fn draw_button(
     );
 }
 
+#[cfg(windows)]
+static DROPPED_FILES: std::sync::Mutex<Vec<PathBuf>> = std::sync::Mutex::new(Vec::new());
+
+#[cfg(windows)]
+static WINDOW_PROC: std::sync::atomic::AtomicIsize = std::sync::atomic::AtomicIsize::new(0);
+
+#[cfg(windows)]
+fn accept_file_drop(window: &Window) {
+    use std::ffi::c_void;
+    use std::sync::atomic::Ordering;
+    #[link(name = "user32")]
+    extern "system" {
+        fn SetWindowLongPtrW(hwnd: *mut c_void, idx: i32, val: isize) -> isize;
+    }
+    #[link(name = "shell32")]
+    extern "system" {
+        fn DragAcceptFiles(hwnd: *mut c_void, accept: i32);
+    }
+    const GWLP_WNDPROC: i32 = -4;
+    unsafe {
+        let hwnd = window.get_window_handle() as *mut c_void;
+        DragAcceptFiles(hwnd, 1);
+        let previous = SetWindowLongPtrW(hwnd, GWLP_WNDPROC, drop_proc as *const () as isize);
+        WINDOW_PROC.store(previous, Ordering::SeqCst);
+    }
+}
+
+#[cfg(windows)]
+unsafe extern "system" fn drop_proc(
+    hwnd: *mut std::ffi::c_void,
+    msg: u32,
+    wparam: usize,
+    lparam: isize,
+) -> isize {
+    use std::ffi::{c_void, OsString};
+    use std::os::windows::ffi::OsStringExt;
+    use std::sync::atomic::Ordering;
+    #[link(name = "user32")]
+    extern "system" {
+        fn CallWindowProcW(
+            proc: isize,
+            hwnd: *mut c_void,
+            msg: u32,
+            wparam: usize,
+            lparam: isize,
+        ) -> isize;
+    }
+    #[link(name = "shell32")]
+    extern "system" {
+        fn DragQueryFileW(drop: *mut c_void, idx: u32, buf: *mut u16, len: u32) -> u32;
+        fn DragFinish(drop: *mut c_void);
+    }
+    const WM_DROPFILES: u32 = 0x0233;
+    if msg != WM_DROPFILES {
+        return CallWindowProcW(
+            WINDOW_PROC.load(Ordering::SeqCst),
+            hwnd,
+            msg,
+            wparam,
+            lparam,
+        );
+    }
+    let handle = wparam as *mut c_void;
+    let count = DragQueryFileW(handle, u32::MAX, std::ptr::null_mut(), 0);
+    let mut files = DROPPED_FILES.lock().unwrap();
+    for idx in 0..count {
+        let len = DragQueryFileW(handle, idx, std::ptr::null_mut(), 0);
+        let mut name = vec![0u16; len as usize + 1];
+        DragQueryFileW(handle, idx, name.as_mut_ptr(), len + 1);
+        files.push(PathBuf::from(OsString::from_wide(&name[..len as usize])));
+    }
+    DragFinish(handle);
+    0
+}
+
+#[cfg(windows)]
+fn take_dropped_files() -> Vec<PathBuf> {
+    std::mem::take(&mut *DROPPED_FILES.lock().unwrap())
+}
+
+#[cfg(target_os = "linux")]
+struct DropTarget {
+    xlib: x11_dl::xlib::Xlib,
+    display: *mut x11_dl::xlib::Display,
+    window: x11_dl::xlib::Window,
+    proxy: x11_dl::xlib::Window,
+    source: x11_dl::xlib::Window,
+    accepting: bool,
+    enter: x11_dl::xlib::Atom,
+    position: x11_dl::xlib::Atom,
+    status: x11_dl::xlib::Atom,
+    drop: x11_dl::xlib::Atom,
+    finished: x11_dl::xlib::Atom,
+    selection: x11_dl::xlib::Atom,
+    type_list: x11_dl::xlib::Atom,
+    uri_list: x11_dl::xlib::Atom,
+    copy: x11_dl::xlib::Atom,
+}
+
+#[cfg(target_os = "linux")]
+thread_local! {
+    static DROP_TARGET: std::cell::RefCell<Option<DropTarget>> = const { std::cell::RefCell::new(None) };
+}
+
+#[cfg(target_os = "linux")]
+fn accept_file_drop(window: &Window) {
+    use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+    use std::ffi::CString;
+    use x11_dl::xlib;
+    let window = match window.window_handle().map(|h| h.as_raw()) {
+        Ok(RawWindowHandle::Xlib(handle)) => handle.window,
+        _ => return,
+    };
+    let Ok(xlib) = xlib::Xlib::open() else {
+        return;
+    };
+    unsafe {
+        (xlib.XSetErrorHandler)(Some(ignore_x_error));
+        let display = (xlib.XOpenDisplay)(std::ptr::null());
+        if display.is_null() {
+            return;
+        }
+        let atom = |name: &str| {
+            let name = CString::new(name).unwrap();
+            (xlib.XInternAtom)(display, name.as_ptr(), 0)
+        };
+        let root = (xlib.XDefaultRootWindow)(display);
+        let proxy = (xlib.XCreateSimpleWindow)(display, root, 0, 0, 1, 1, 0, 0, 0);
+        let version: std::ffi::c_ulong = 5;
+        for target in [window, proxy] {
+            let (aware, proxy_atom) = (atom("XdndAware"), atom("XdndProxy"));
+            let set = |property, kind, value: *const std::ffi::c_ulong| {
+                (xlib.XChangeProperty)(
+                    display,
+                    target,
+                    property,
+                    kind,
+                    32,
+                    xlib::PropModeReplace,
+                    value as *const u8,
+                    1,
+                )
+            };
+            set(aware, xlib::XA_ATOM, &version);
+            set(proxy_atom, xlib::XA_WINDOW, &proxy);
+        }
+        (xlib.XFlush)(display);
+        let target = DropTarget {
+            display,
+            window,
+            proxy,
+            source: 0,
+            accepting: false,
+            enter: atom("XdndEnter"),
+            position: atom("XdndPosition"),
+            status: atom("XdndStatus"),
+            drop: atom("XdndDrop"),
+            finished: atom("XdndFinished"),
+            selection: atom("XdndSelection"),
+            type_list: atom("XdndTypeList"),
+            uri_list: atom("text/uri-list"),
+            copy: atom("XdndActionCopy"),
+            xlib,
+        };
+        DROP_TARGET.with_borrow_mut(|slot| *slot = Some(target));
+    }
+}
+
+#[cfg(target_os = "linux")]
+unsafe extern "C" fn ignore_x_error(
+    _: *mut x11_dl::xlib::Display,
+    _: *mut x11_dl::xlib::XErrorEvent,
+) -> std::ffi::c_int {
+    0
+}
+
+#[cfg(target_os = "linux")]
+impl DropTarget {
+    fn poll(&mut self) -> Vec<PathBuf> {
+        use x11_dl::xlib;
+        let mut files = vec![];
+        unsafe {
+            while (self.xlib.XPending)(self.display) > 0 {
+                let mut event: xlib::XEvent = std::mem::zeroed();
+                (self.xlib.XNextEvent)(self.display, &mut event);
+                match event.get_type() {
+                    xlib::ClientMessage => {
+                        let message = event.client_message;
+                        let source = message.data.get_long(0) as xlib::Window;
+                        if message.message_type == self.enter {
+                            self.accepting = self.offers_uri_list(&message);
+                        } else if message.message_type == self.position {
+                            let action = if self.accepting { self.copy } else { 0 };
+                            let data = [self.window as _, self.accepting as _, 0, 0, action as _];
+                            self.send(source, self.status, data);
+                        } else if message.message_type == self.drop && self.accepting {
+                            self.source = source;
+                            (self.xlib.XConvertSelection)(
+                                self.display,
+                                self.selection,
+                                self.uri_list,
+                                self.selection,
+                                self.proxy,
+                                message.data.get_long(2) as xlib::Time,
+                            );
+                        } else if message.message_type == self.drop {
+                            self.send(source, self.finished, [self.window as _, 0, 0, 0, 0]);
+                        }
+                    }
+                    xlib::SelectionNotify if event.selection.property != 0 => {
+                        let uris = self.read_property(self.proxy, event.selection.property, true);
+                        let uris = String::from_utf8_lossy(&uris);
+                        files.extend(uris.lines().filter_map(file_uri_path));
+                        let data = [self.window as _, 1, self.copy as _, 0, 0];
+                        self.send(self.source, self.finished, data);
+                    }
+                    xlib::SelectionNotify => {
+                        self.send(self.source, self.finished, [self.window as _, 0, 0, 0, 0]);
+                    }
+                    _ => {}
+                }
+            }
+            (self.xlib.XFlush)(self.display);
+        }
+        files
+    }
+
+    unsafe fn offers_uri_list(&self, enter: &x11_dl::xlib::XClientMessageEvent) -> bool {
+        let source = enter.data.get_long(0) as x11_dl::xlib::Window;
+        if enter.data.get_long(1) & 1 == 0 {
+            return (2..5).any(|i| enter.data.get_long(i) as x11_dl::xlib::Atom == self.uri_list);
+        }
+        let size = std::mem::size_of::<x11_dl::xlib::Atom>();
+        let types = self.read_property(source, self.type_list, false);
+        types
+            .chunks_exact(size)
+            .any(|chunk| chunk == self.uri_list.to_ne_bytes())
+    }
+
+    unsafe fn send(
+        &self,
+        to: x11_dl::xlib::Window,
+        kind: x11_dl::xlib::Atom,
+        data: [std::ffi::c_long; 5],
+    ) {
+        use x11_dl::xlib;
+        let mut event: xlib::XEvent = std::mem::zeroed();
+        event.client_message = xlib::XClientMessageEvent {
+            type_: xlib::ClientMessage,
+            serial: 0,
+            send_event: 1,
+            display: self.display,
+            window: to,
+            message_type: kind,
+            format: 32,
+            data: xlib::ClientMessageData::from(data),
+        };
+        (self.xlib.XSendEvent)(self.display, to, 0, xlib::NoEventMask, &mut event);
+    }
+
+    unsafe fn read_property(
+        &self,
+        window: x11_dl::xlib::Window,
+        property: x11_dl::xlib::Atom,
+        delete: bool,
+    ) -> Vec<u8> {
+        let (mut kind, mut format, mut items, mut remaining) = (0, 0, 0, 0);
+        let mut data = std::ptr::null_mut();
+        (self.xlib.XGetWindowProperty)(
+            self.display,
+            window,
+            property,
+            0,
+            i32::MAX as _,
+            delete as _,
+            x11_dl::xlib::AnyPropertyType as _,
+            &mut kind,
+            &mut format,
+            &mut items,
+            &mut remaining,
+            &mut data,
+        );
+        if data.is_null() {
+            return vec![];
+        }
+        let unit = match format {
+            32 => std::mem::size_of::<std::ffi::c_long>(),
+            16 => 2,
+            _ => 1,
+        };
+        let bytes = std::slice::from_raw_parts(data, items as usize * unit).to_vec();
+        (self.xlib.XFree)(data as *mut _);
+        bytes
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn file_uri_path(uri: &str) -> Option<PathBuf> {
+    use std::os::unix::ffi::OsStringExt;
+    let rest = uri.trim().strip_prefix("file://")?;
+    let path = &rest[rest.find('/')?..];
+    let mut bytes = vec![];
+    let mut i = 0;
+    while i < path.len() {
+        let escaped = path
+            .get(i + 1..i + 3)
+            .filter(|_| path.as_bytes()[i] == b'%');
+        match escaped.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+            Some(byte) => {
+                bytes.push(byte);
+                i += 3;
+            }
+            None => {
+                bytes.push(path.as_bytes()[i]);
+                i += 1;
+            }
+        }
+    }
+    Some(PathBuf::from(std::ffi::OsString::from_vec(bytes)))
+}
+
+#[cfg(target_os = "linux")]
+fn take_dropped_files() -> Vec<PathBuf> {
+    DROP_TARGET.with_borrow_mut(|target| target.as_mut().map(DropTarget::poll).unwrap_or_default())
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+fn take_dropped_files() -> Vec<PathBuf> {
+    vec![]
+}
+
 #[cfg(windows)]
 fn setup_window(window: &Window) {
     use std::ffi::c_void;
@@ -651,7 +2662,8 @@ fn setup_window(window: &Window) {
     }
 }
 
-fn set_fullscreen(window: &Window, fullscreen: bool) {
+#[cfg_attr(not(windows), allow(unused_variables))]
+fn set_fullscreen(window: &Window, fullscreen: bool, settings: &Settings) {
     #[cfg(windows)]
     {
         use std::ffi::c_void;
@@ -709,17 +2721,25 @@ fn set_fullscreen(window: &Window, fullscreen: bool) {
                 );
             } else {
                 SetWindowLongPtrW(hwnd, GWL_STYLE, style | WS_OVERLAPPEDWINDOW);
-                SetWindowPos(hwnd, std::ptr::null_mut(), 100, 100, 1024, 600, 0x0040);
+                let (x, y) = settings.position.unwrap_or((100, 100));
+                SetWindowPos(
+                    hwnd,
+                    std::ptr::null_mut(),
+                    x as i32,
+                    y as i32,
+                    settings.width as i32,
+                    settings.height as i32,
+                    0x0040,
+                );
             }
         }
     }
 
     #[cfg(target_os = "linux")]
     {
-        use std::ffi::c_void;
         use std::process::Command;
         let handle = window.get_window_handle();
-        if handle != std::ptr::null_mut() as *mut c_void {
+        if !handle.is_null() {
             let window_id = handle as usize;
             if fullscreen {
                 let _ = Command::new("wmctrl")
@@ -745,3 +2765,25 @@ fn set_fullscreen(window: &Window, fullscreen: bool) {
         }
     }
 }
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropped_uris() {
+        let cases = [
+            ("file:///home/me/song.lrc", Some("/home/me/song.lrc")),
+            (
+                "file://host/My%20Songs/caf%C3%A9.mp3\r",
+                Some("/My Songs/café.mp3"),
+            ),
+            ("file:///100%.lrc", Some("/100%.lrc")),
+            ("https://example.com/song.lrc", None),
+            ("# comment", None),
+        ];
+        for (uri, expected) in cases {
+            assert_eq!(file_uri_path(uri), expected.map(PathBuf::from), "{}", uri);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use crate::config::{parse_entries, parse_finite};
+use crate::settings::parse_secs;
+use crate::theme::Theme;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Default)]
+pub struct Session {
+    pub lyrics: Option<PathBuf>,
+    pub audio: Option<PathBuf>,
+    pub offset: f32,
+    pub look_ahead: Option<f32>,
+    pub pre_roll: Option<f32>,
+    pub theme: Option<String>,
+    pub position: f32,
+}
+
+impl Session {
+    pub fn parse(content: &str, path: &Path) -> Self {
+        let base = path.parent().unwrap_or(Path::new(""));
+        let path_value = |value: &str| (!value.is_empty()).then(|| base.join(value));
+        let mut session = Self::default();
+        for (key, value) in parse_entries(content) {
+            match key {
+                "lyrics" => session.lyrics = path_value(value),
+                "audio" => session.audio = path_value(value),
+                "offset" => session.offset = parse_finite(value).unwrap_or(0.0),
+                "look_ahead" => session.look_ahead = parse_secs(value),
+                "pre_roll" => session.pre_roll = parse_secs(value),
+                "theme" if Theme::named(value).is_some() => {
+                    session.theme = Some(value.to_ascii_lowercase())
+                }
+                "position" => session.position = parse_finite(value).unwrap_or(0.0).max(0.0),
+                _ => {}
+            }
+        }
+        session
+    }
+
+    pub fn to_string_in(&self, dir: &Path) -> String {
+        let relative = |path: &Path| path.strip_prefix(dir).unwrap_or(path).display().to_string();
+        let mut out = String::new();
+        if let Some(lyrics) = &self.lyrics {
+            let _ = writeln!(out, "lyrics = {}", relative(lyrics));
+        }
+        if let Some(audio) = &self.audio {
+            let _ = writeln!(out, "audio = {}", relative(audio));
+        }
+        let _ = writeln!(out, "offset = {}", self.offset);
+        if let Some(look_ahead) = self.look_ahead {
+            let _ = writeln!(out, "look_ahead = {}", look_ahead);
+        }
+        if let Some(pre_roll) = self.pre_roll {
+            let _ = writeln!(out, "pre_roll = {}", pre_roll);
+        }
+        if let Some(theme) = &self.theme {
+            let _ = writeln!(out, "theme = {}", theme);
+        }
+        let _ = writeln!(out, "position = {}", self.position);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_write() {
+        let content = "lyrics = song.lrc\naudio = \noffset = 0.3\nlook_ahead = 12\n\
+                       pre_roll = -1\ntheme = High-Contrast\nposition = 42.5\n";
+        let session = Session::parse(content, Path::new("/shows/song.session"));
+        assert_eq!(session.lyrics, Some(PathBuf::from("/shows/song.lrc")));
+        assert_eq!(session.audio, None);
+        assert_eq!(session.look_ahead, Some(10.0));
+        assert_eq!(session.pre_roll, Some(0.0));
+        assert_eq!(session.theme.as_deref(), Some("high-contrast"));
+        assert_eq!(
+            session.to_string_in(Path::new("/shows")),
+            "lyrics = song.lrc\noffset = 0.3\nlook_ahead = 10\npre_roll = 0\n\
+             theme = high-contrast\nposition = 42.5\n"
+        );
+        let invalid = Session::parse(
+            "offset = NaN\nlook_ahead = inf\npre_roll = NaN\ntheme = neon\nposition = inf\n",
+            Path::new(""),
+        );
+        assert_eq!((invalid.look_ahead, invalid.pre_roll), (None, None));
+        assert_eq!(invalid.theme, None);
+        assert_eq!((invalid.offset, invalid.position), (0.0, 0.0));
+    }
+}
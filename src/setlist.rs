@@ -0,0 +1,167 @@
+use crate::config::parse_finite;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Default)]
+pub struct SetlistEntry {
+    pub title: Option<String>,
+    pub lyrics: PathBuf,
+    pub audio: Option<PathBuf>,
+    pub offset: f32,
+    pub notes: String,
+}
+
+impl SetlistEntry {
+    pub fn title(&self) -> String {
+        self.title.clone().unwrap_or_else(|| {
+            self.lyrics
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+    }
+
+    pub fn label(&self) -> String {
+        match self.notes.is_empty() {
+            true => self.title(),
+            false => format!("{} - {}", self.title(), self.notes),
+        }
+    }
+
+    fn set(&mut self, key: &str, value: &str, base: &Path) {
+        match key {
+            "title" => self.title = (!value.is_empty()).then(|| value.to_string()),
+            "lyrics" => self.lyrics = base.join(value),
+            "audio" => self.audio = (!value.is_empty()).then(|| base.join(value)),
+            "offset" => self.offset = parse_finite(value).unwrap_or(0.0),
+            "notes" => self.notes = value.to_string(),
+            _ => {}
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Setlist {
+    pub path: Option<PathBuf>,
+    pub entries: Vec<SetlistEntry>,
+    pub current: usize,
+    pub auto_advance: bool,
+}
+
+impl Setlist {
+    pub fn parse(content: &str, path: &Path) -> Self {
+        let base = path.parent().unwrap_or(Path::new(""));
+        let mut setlist = Self {
+            path: Some(path.to_path_buf()),
+            ..Self::default()
+        };
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line == "[song]" {
+                setlist.entries.push(SetlistEntry::default());
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            match setlist.entries.last_mut() {
+                Some(entry) => entry.set(key, value, base),
+                None if key == "auto_advance" => setlist.auto_advance = value == "true",
+                None => {}
+            }
+        }
+        setlist.entries.retain(|e| !e.lyrics.as_os_str().is_empty());
+        setlist
+    }
+
+    pub fn current(&self) -> Option<&SetlistEntry> {
+        self.entries.get(self.current)
+    }
+
+    pub fn upcoming(&self) -> Option<&SetlistEntry> {
+        self.entries.get(self.current + 1)
+    }
+
+    pub fn to_string_in(&self, dir: &Path) -> String {
+        let relative = |path: &Path| path.strip_prefix(dir).unwrap_or(path).display().to_string();
+        let mut out = String::new();
+        let _ = writeln!(out, "auto_advance = {}", self.auto_advance);
+        for entry in &self.entries {
+            let _ = writeln!(out, "\n[song]");
+            if let Some(title) = &entry.title {
+                let _ = writeln!(out, "title = {}", title);
+            }
+            let _ = writeln!(out, "lyrics = {}", relative(&entry.lyrics));
+            if let Some(audio) = &entry.audio {
+                let _ = writeln!(out, "audio = {}", relative(audio));
+            }
+            if entry.offset != 0.0 {
+                let _ = writeln!(out, "offset = {}", entry.offset);
+            }
+            if !entry.notes.is_empty() {
+                let _ = writeln!(out, "notes = {}", entry.notes);
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_write() {
+        let content = "auto_advance = true\ntitle = ignored\n\n[song]\n# opener\n\
+                       lyrics = opener.lrc\naudio = opener.mp3\noffset = 0.3\nnotes = Capo 2\n\
+                       [song]\ntitle = Ballad\nlyrics = slow/ballad.lrc\noffset = NaN\n\
+                       [song]\naudio = orphan.mp3\n[song]\nlyrics = encore.lrc\noffset = inf\n";
+        let setlist = Setlist::parse(content, Path::new("/show/tour.setlist"));
+        assert!(setlist.auto_advance);
+        let entries: Vec<_> = setlist
+            .entries
+            .iter()
+            .map(|e| (e.label(), e.lyrics.clone(), e.audio.clone(), e.offset))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                (
+                    "opener - Capo 2".to_string(),
+                    PathBuf::from("/show/opener.lrc"),
+                    Some(PathBuf::from("/show/opener.mp3")),
+                    0.3
+                ),
+                (
+                    "Ballad".to_string(),
+                    PathBuf::from("/show/slow/ballad.lrc"),
+                    None,
+                    0.0
+                ),
+                (
+                    "encore".to_string(),
+                    PathBuf::from("/show/encore.lrc"),
+                    None,
+                    0.0
+                ),
+            ]
+        );
+        assert_eq!(
+            setlist.upcoming().map(|e| e.title()).as_deref(),
+            Some("Ballad")
+        );
+        let written = setlist.to_string_in(Path::new("/show"));
+        assert_eq!(
+            written,
+            "auto_advance = true\n\n[song]\nlyrics = opener.lrc\naudio = opener.mp3\n\
+             offset = 0.3\nnotes = Capo 2\n\n[song]\ntitle = Ballad\nlyrics = slow/ballad.lrc\n\
+             \n[song]\nlyrics = encore.lrc\n"
+        );
+        let reparsed = Setlist::parse(&written, Path::new("/show/tour.setlist"));
+        assert_eq!(reparsed.to_string_in(Path::new("/show")), written);
+    }
+}
@@ -0,0 +1,378 @@
+use crate::config::{config_dir, parse_finite, read_config};
+use crate::theme::{Theme, DARK};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+const FILE_NAME: &str = "settings.conf";
+const MAX_RECENT: usize = 20;
+
+#[derive(Default)]
+pub struct SongSettings {
+    pub look_ahead: Option<f32>,
+    pub pre_roll: Option<f32>,
+}
+
+impl SongSettings {
+    fn is_empty(&self) -> bool {
+        self.look_ahead.is_none() && self.pre_roll.is_none()
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        match key {
+            "look_ahead" => self.look_ahead = parse_secs(value),
+            "pre_roll" => self.pre_roll = parse_secs(value),
+            _ => {}
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Recent {
+    pub lyrics: Option<PathBuf>,
+    pub audio: Option<PathBuf>,
+}
+
+impl Recent {
+    pub fn label(&self) -> String {
+        let name = |path: &PathBuf| {
+            path.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default()
+        };
+        match (&self.lyrics, &self.audio) {
+            (Some(lyrics), Some(audio)) => format!("{} + {}", name(lyrics), name(audio)),
+            (Some(path), None) | (None, Some(path)) => name(path),
+            (None, None) => String::new(),
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        let (lyrics, audio) = value.split_once('|').unwrap_or((value, ""));
+        let recent = Self {
+            lyrics: path_value(lyrics.trim()),
+            audio: path_value(audio.trim()),
+        };
+        (recent.lyrics.is_some() || recent.audio.is_some()).then_some(recent)
+    }
+}
+
+impl std::fmt::Display for Recent {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(lyrics) = &self.lyrics {
+            write!(f, "{}", lyrics.display())?;
+        }
+        if let Some(audio) = &self.audio {
+            write!(f, " | {}", audio.display())?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Settings {
+    pub width: usize,
+    pub height: usize,
+    pub position: Option<(isize, isize)>,
+    pub fullscreen: bool,
+    pub theme: String,
+    pub colors: Vec<(String, String)>,
+    pub font: Option<PathBuf>,
+    pub look_ahead: f32,
+    pub pre_roll: f32,
+    pub volume: f32,
+    pub output_device: Option<String>,
+    pub lyrics_dir: Option<PathBuf>,
+    pub audio_dir: Option<PathBuf>,
+    pub recent: Vec<Recent>,
+    pub pair_files: String,
+    songs: Vec<(PathBuf, SongSettings)>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            width: 1024,
+            height: 600,
+            position: None,
+            fullscreen: false,
+            theme: "dark".to_string(),
+            colors: vec![],
+            font: None,
+            look_ahead: 0.5,
+            pre_roll: 1.0,
+            volume: 1.0,
+            output_device: None,
+            lyrics_dir: None,
+            audio_dir: None,
+            recent: vec![],
+            pair_files: "ask".to_string(),
+            songs: vec![],
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        let mut settings = Self::default();
+        if let Some(content) = read_config(FILE_NAME) {
+            settings.apply(&content);
+        }
+        settings
+    }
+
+    pub fn save(&self) {
+        let Some(dir) = config_dir() else { return };
+        if std::fs::create_dir_all(&dir).is_ok() {
+            let _ = std::fs::write(dir.join(FILE_NAME), self.to_string());
+        }
+    }
+
+    pub fn theme(&self, name: &str) -> Theme {
+        let mut theme = Theme::named(name).unwrap_or(DARK);
+        for (key, value) in &self.colors {
+            theme.set_color(key, value);
+        }
+        theme
+    }
+
+    pub fn add_recent(&mut self, lyrics: Option<&Path>, audio: Option<&Path>) {
+        let mut entry = Recent {
+            lyrics: lyrics.map(Path::to_path_buf),
+            audio: audio.map(Path::to_path_buf),
+        };
+        let same = |r: &Recent| match &entry.lyrics {
+            Some(lyrics) => r.lyrics.as_ref() == Some(lyrics),
+            None => r.lyrics.is_none() && r.audio == entry.audio,
+        };
+        if let Some(pos) = self.recent.iter().position(same) {
+            let old = self.recent.remove(pos);
+            if entry.audio.is_none() {
+                entry.audio = old.audio;
+            }
+        }
+        if entry.lyrics.is_some() {
+            self.recent
+                .retain(|r| r.lyrics.is_some() || r.audio != entry.audio);
+        }
+        self.recent.insert(0, entry);
+        self.recent.truncate(MAX_RECENT);
+    }
+
+    pub fn song(&self, path: &Path) -> Option<&SongSettings> {
+        self.songs.iter().find(|(p, _)| p == path).map(|(_, s)| s)
+    }
+
+    pub fn song_mut(&mut self, path: &Path) -> &mut SongSettings {
+        let pos = match self.songs.iter().position(|(p, _)| p == path) {
+            Some(pos) => pos,
+            None => {
+                self.songs
+                    .push((path.to_path_buf(), SongSettings::default()));
+                self.songs.len() - 1
+            }
+        };
+        &mut self.songs[pos].1
+    }
+
+    fn apply(&mut self, content: &str) {
+        let mut song: Option<usize> = None;
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(path) = line
+                .strip_prefix("[song ")
+                .and_then(|l| l.strip_suffix(']'))
+            {
+                self.songs
+                    .push((PathBuf::from(path.trim()), SongSettings::default()));
+                song = Some(self.songs.len() - 1);
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            match song {
+                Some(idx) => self.songs[idx].1.set(key, value),
+                None => self.set(key, value),
+            }
+        }
+        self.songs.retain(|(_, s)| !s.is_empty());
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        match key {
+            "width" => self.width = value.parse().unwrap_or(self.width).max(200),
+            "height" => self.height = value.parse().unwrap_or(self.height).max(200),
+            "position" => {
+                self.position = value
+                    .split_once(',')
+                    .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)))
+            }
+            "fullscreen" => self.fullscreen = value == "true",
+            "theme" => self.theme = value.to_string(),
+            "font" => self.font = path_value(value),
+            "look_ahead" => set_secs(&mut self.look_ahead, value),
+            "pre_roll" => set_secs(&mut self.pre_roll, value),
+            "volume" => {
+                if let Some(volume) = parse_finite(value) {
+                    self.volume = volume.clamp(0.0, 1.0);
+                }
+            }
+            "pair_files" => self.pair_files = value.to_ascii_lowercase(),
+            "output_device" => self.output_device = (!value.is_empty()).then(|| value.to_string()),
+            "lyrics_dir" => self.lyrics_dir = path_value(value),
+            "audio_dir" => self.audio_dir = path_value(value),
+            "recent" => {
+                if self.recent.len() < MAX_RECENT {
+                    self.recent.extend(Recent::parse(value));
+                }
+            }
+            _ => {
+                if let Some(color) = key.strip_prefix("color.") {
+                    self.colors.push((color.to_string(), value.to_string()));
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Settings {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "width = {}", self.width)?;
+        writeln!(f, "height = {}", self.height)?;
+        if let Some((x, y)) = self.position {
+            writeln!(f, "position = {}, {}", x, y)?;
+        }
+        writeln!(f, "fullscreen = {}", self.fullscreen)?;
+        writeln!(f, "theme = {}", self.theme)?;
+        for (key, value) in &self.colors {
+            writeln!(f, "color.{} = {}", key, value)?;
+        }
+        if let Some(font) = &self.font {
+            writeln!(f, "font = {}", font.display())?;
+        }
+        writeln!(f, "look_ahead = {}", self.look_ahead)?;
+        writeln!(f, "pre_roll = {}", self.pre_roll)?;
+        writeln!(f, "volume = {}", self.volume)?;
+        if let Some(device) = &self.output_device {
+            writeln!(f, "output_device = {}", device)?;
+        }
+        if let Some(dir) = &self.lyrics_dir {
+            writeln!(f, "lyrics_dir = {}", dir.display())?;
+        }
+        if let Some(dir) = &self.audio_dir {
+            writeln!(f, "audio_dir = {}", dir.display())?;
+        }
+        writeln!(f, "pair_files = {}", self.pair_files)?;
+        for recent in &self.recent {
+            writeln!(f, "recent = {}", recent)?;
+        }
+        for (path, song) in self.songs.iter().filter(|(_, s)| !s.is_empty()) {
+            let mut body = String::new();
+            if let Some(look_ahead) = song.look_ahead {
+                let _ = writeln!(body, "look_ahead = {}", look_ahead);
+            }
+            if let Some(pre_roll) = song.pre_roll {
+                let _ = writeln!(body, "pre_roll = {}", pre_roll);
+            }
+            write!(f, "\n[song {}]\n{}", path.display(), body)?;
+        }
+        Ok(())
+    }
+}
+
+fn path_value(value: &str) -> Option<PathBuf> {
+    (!value.is_empty()).then(|| PathBuf::from(value))
+}
+
+fn set_secs(field: &mut f32, value: &str) {
+    if let Some(secs) = parse_secs(value) {
+        *field = secs;
+    }
+}
+
+pub fn parse_secs(value: &str) -> Option<f32> {
+    Some(parse_finite(value)?.clamp(0.0, 10.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> Settings {
+        let mut settings = Settings::default();
+        settings.apply(content);
+        settings
+    }
+
+    #[test]
+    fn values() {
+        let cases = [
+            ("width = 50", "width = 200"),
+            ("width = wide", "width = 1024"),
+            ("position = 10, -20", "position = 10, -20"),
+            ("fullscreen = yes", "fullscreen = false"),
+            ("look_ahead = 2.5", "look_ahead = 2.5"),
+            ("look_ahead = 99", "look_ahead = 10"),
+            ("pre_roll = NaN", "pre_roll = 1"),
+            ("color.bg = #102030", "color.bg = #102030"),
+            ("pair_files = Always", "pair_files = always"),
+            ("volume = 1.5", "volume = 1"),
+            ("volume = NaN", "volume = 1"),
+        ];
+        for (content, expected) in cases {
+            let written = parse(content).to_string();
+            assert!(written.lines().any(|l| l == expected), "{}", content);
+        }
+        assert_eq!(parse("position = 10").position, None);
+    }
+
+    #[test]
+    fn recent() {
+        let cases = [
+            ("a.lrc | b.mp3", Some((Some("a.lrc"), Some("b.mp3")))),
+            (" a.lrc ", Some((Some("a.lrc"), None))),
+            ("| b.mp3", Some((None, Some("b.mp3")))),
+            ("|", None),
+            ("", None),
+        ];
+        for (value, expected) in cases {
+            let parsed = Recent::parse(value);
+            let paths = parsed
+                .as_ref()
+                .map(|r| (r.lyrics.as_deref(), r.audio.as_deref()));
+            let expected = expected.map(|(l, a)| (l.map(Path::new), a.map(Path::new)));
+            assert_eq!(paths, expected, "{}", value);
+            if let Some(recent) = parsed {
+                assert!(Recent::parse(&recent.to_string()) == Some(recent));
+            }
+        }
+        let content: String = (0..30).map(|i| format!("recent = {}.lrc\n", i)).collect();
+        let settings = parse(&content);
+        assert_eq!(settings.recent.len(), MAX_RECENT);
+        assert_eq!(settings.recent[0].label(), "0.lrc");
+        let mut settings = parse("recent = a.lrc | a.mp3\nrecent = | b.mp3\n");
+        settings.add_recent(None, Some(Path::new("b.mp3")));
+        settings.add_recent(Some(Path::new("b.lrc")), Some(Path::new("b.mp3")));
+        settings.add_recent(Some(Path::new("a.lrc")), None);
+        let labels: Vec<_> = settings.recent.iter().map(Recent::label).collect();
+        assert_eq!(labels, ["a.lrc + a.mp3", "b.lrc + b.mp3"]);
+    }
+
+    #[test]
+    fn songs() {
+        let settings = parse(
+            "theme = light\n\n[song /music/a.lrc]\nlook_ahead = 1.5\npre_roll = -3\n\
+             theme = dark\n\n[song /music/b.lrc]\nlook_ahead = NaN\n",
+        );
+        assert_eq!(settings.theme, "light");
+        let song = settings.song(Path::new("/music/a.lrc")).unwrap();
+        assert_eq!((song.look_ahead, song.pre_roll), (Some(1.5), Some(0.0)));
+        assert!(settings.song(Path::new("/music/b.lrc")).is_none());
+        let written = parse(&settings.to_string()).to_string();
+        assert_eq!(written, settings.to_string());
+        assert!(written.ends_with("\n[song /music/a.lrc]\nlook_ahead = 1.5\npre_roll = 0\n"));
+    }
+}
@@ -0,0 +1,311 @@
+use rodio::source::SeekError;
+use rodio::{ChannelCount, SampleRate, Source};
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Clone)]
+pub struct StretchHandle {
+    speed: Arc<AtomicU32>,
+    position: Arc<AtomicU64>,
+}
+
+impl StretchHandle {
+    pub fn new(speed: f32) -> Self {
+        Self {
+            speed: Arc::new(AtomicU32::new(speed.to_bits())),
+            position: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn set_speed(&self, speed: f32) {
+        self.speed.store(speed.to_bits(), Ordering::Relaxed);
+    }
+
+    fn speed(&self) -> f32 {
+        f32::from_bits(self.speed.load(Ordering::Relaxed))
+    }
+
+    pub fn position(&self) -> f32 {
+        self.position.load(Ordering::Relaxed) as f32 / 1_000_000.0
+    }
+
+    fn set_position(&self, frame: i64, rate: u32) {
+        let micros = frame.max(0) as u64 * 1_000_000 / rate.max(1) as u64;
+        self.position.store(micros, Ordering::Relaxed);
+    }
+}
+
+/// WSOLA time-stretcher that every song plays through, even at 100% speed.
+///
+/// Output starts with one hop (20 ms) of silence after opening or seeking, so
+/// the first window can overlap-add; at 100% the audio after it is passed
+/// through unchanged. The handle's position stays on the source timeline.
+pub struct TimeStretch<S> {
+    input: S,
+    handle: StretchHandle,
+    channels: usize,
+    rate: u32,
+    window: Vec<f32>,
+    hop: usize,
+    tolerance: i64,
+    buffer: Vec<f32>,
+    buffer_start: i64,
+    exhausted: bool,
+    analysis: f64,
+    natural: i64,
+    overlap: Vec<f32>,
+    output: Vec<f32>,
+    output_idx: usize,
+    output_frame: i64,
+}
+
+impl<S: Source> TimeStretch<S> {
+    pub fn new(input: S, handle: StretchHandle) -> Self {
+        let channels = input.channels().max(1) as usize;
+        let rate = input.sample_rate();
+        let hop = (rate as usize / 50).max(64);
+        let window = (0..hop * 2)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / (hop * 2) as f32).cos())
+            .collect();
+        let mut stretch = Self {
+            input,
+            handle,
+            channels,
+            rate,
+            window,
+            hop,
+            tolerance: hop as i64 / 2,
+            buffer: vec![],
+            buffer_start: 0,
+            exhausted: false,
+            analysis: 0.0,
+            natural: 0,
+            overlap: vec![],
+            output: vec![],
+            output_idx: 0,
+            output_frame: 0,
+        };
+        stretch.reset(0);
+        stretch
+    }
+
+    fn reset(&mut self, frame: i64) {
+        let start = frame - self.hop as i64;
+        self.buffer = vec![0.0; self.hop * self.channels];
+        self.buffer_start = start;
+        self.exhausted = false;
+        self.analysis = start as f64;
+        self.natural = start;
+        self.overlap = vec![0.0; self.hop * self.channels];
+        self.output.clear();
+        self.output_idx = 0;
+        self.output_frame = start;
+        self.handle.set_position(frame, self.rate);
+    }
+
+    fn buffer_end(&self) -> i64 {
+        self.buffer_start + (self.buffer.len() / self.channels) as i64
+    }
+
+    fn fill(&mut self, until: i64) {
+        while !self.exhausted && self.buffer_end() < until {
+            for _ in 0..self.channels {
+                match self.input.next() {
+                    Some(sample) => self.buffer.push(sample),
+                    None => {
+                        self.exhausted = true;
+                        self.buffer.push(0.0);
+                    }
+                }
+            }
+        }
+    }
+
+    fn sample(&self, frame: i64, channel: usize) -> f32 {
+        let idx = frame - self.buffer_start;
+        if idx < 0 {
+            return 0.0;
+        }
+        self.buffer
+            .get(idx as usize * self.channels + channel)
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    fn mono(&self, frame: i64) -> f32 {
+        (0..self.channels).map(|c| self.sample(frame, c)).sum()
+    }
+
+    fn similarity(&self, candidate: i64) -> f32 {
+        let (mut cross, mut energy) = (0.0, 1e-9);
+        for i in (0..self.hop as i64).step_by(8) {
+            let x = self.mono(candidate + i);
+            cross += x * self.mono(self.natural + i);
+            energy += x * x;
+        }
+        cross / energy.sqrt()
+    }
+
+    fn search(&self, target: i64) -> i64 {
+        let low = (target - self.tolerance).max(self.buffer_start);
+        let high = (target + self.tolerance).max(low);
+        let best_in = |from: i64, to: i64, step: usize| {
+            (from..=to)
+                .step_by(step)
+                .map(|c| (c, self.similarity(c)))
+                .fold((from, f32::MIN), |a, b| if b.1 > a.1 { b } else { a })
+                .0
+        };
+        let coarse = best_in(low, high, 4);
+        best_in((coarse - 3).max(low), (coarse + 3).min(high), 1)
+    }
+
+    fn process_hop(&mut self) -> bool {
+        let speed = self.handle.speed();
+        let hop = self.hop as i64;
+        let target = match speed == 1.0 {
+            true => self.natural,
+            false => self.analysis as i64,
+        };
+        self.fill(target.max(self.natural) + self.tolerance + hop * 2 + 1);
+        if self.exhausted && target.min(self.natural) >= self.buffer_end() {
+            return false;
+        }
+        let best = match speed == 1.0 {
+            true => target,
+            false => self.search(target),
+        };
+        self.output.clear();
+        for i in 0..self.hop {
+            for c in 0..self.channels {
+                let head = self.sample(best + i as i64, c) * self.window[i];
+                self.output.push(self.overlap[i * self.channels + c] + head);
+                let tail = self.sample(best + hop + i as i64, c) * self.window[self.hop + i];
+                self.overlap[i * self.channels + c] = tail;
+            }
+        }
+        self.output_idx = 0;
+        self.output_frame = best;
+        self.natural = best + hop;
+        self.analysis = match speed == 1.0 {
+            true => self.natural as f64,
+            false => self.analysis + self.hop as f64 * speed as f64,
+        };
+        let keep_from = (self.analysis as i64 - self.tolerance).min(self.natural);
+        if keep_from > self.buffer_start {
+            let drop =
+                ((keep_from - self.buffer_start) as usize * self.channels).min(self.buffer.len());
+            self.buffer.drain(..drop);
+            self.buffer_start += (drop / self.channels) as i64;
+        }
+        true
+    }
+}
+
+impl<S: Source> Iterator for TimeStretch<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.output_idx >= self.output.len() && !self.process_hop() {
+            return None;
+        }
+        if self.output_idx.is_multiple_of(self.channels) {
+            let frame = self.output_frame + (self.output_idx / self.channels) as i64;
+            self.handle.set_position(frame, self.rate);
+        }
+        let sample = self.output[self.output_idx];
+        self.output_idx += 1;
+        Some(sample)
+    }
+}
+
+impl<S: Source> Source for TimeStretch<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> ChannelCount {
+        self.channels as ChannelCount
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.reset((pos.as_secs_f64() * self.rate as f64) as i64);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    const RATE: u32 = 8000;
+
+    fn tone(frames: usize) -> Vec<f32> {
+        (0..frames * 2)
+            .map(|i| ((i / 2) as f32 * 440.0 * 2.0 * PI / RATE as f32).sin() * 0.5)
+            .collect()
+    }
+
+    fn stretch(samples: &[f32], speed: f32) -> (TimeStretch<SamplesBuffer>, StretchHandle) {
+        let handle = StretchHandle::new(speed);
+        let source = SamplesBuffer::new(2, RATE, samples.to_vec());
+        (TimeStretch::new(source, handle.clone()), handle)
+    }
+
+    #[test]
+    fn passes_audio_through_at_normal_speed() {
+        let input = tone(RATE as usize);
+        let output: Vec<f32> = stretch(&input, 1.0).0.collect();
+        let lead_in = (RATE as usize / 50) * 2;
+        assert!(output[..lead_in].iter().all(|s| *s == 0.0));
+        let passed = &output[lead_in..lead_in + input.len()];
+        let error = passed
+            .iter()
+            .zip(&input)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f32::max);
+        assert!(error < 1e-5, "{}", error);
+    }
+
+    #[test]
+    fn scales_length_with_speed() {
+        let input = tone(RATE as usize * 2);
+        for speed in [0.5, 0.75, 1.5] {
+            let output = stretch(&input, speed).0.count();
+            let expected = input.len() as f32 / speed;
+            let error = (output as f32 - expected).abs() / expected;
+            assert!(error < 0.02, "{}: {} vs {}", speed, output, expected);
+        }
+    }
+
+    #[test]
+    fn reports_position_after_seek() {
+        let (mut stretch, handle) = stretch(&tone(RATE as usize * 3), 1.5);
+        stretch.by_ref().take(RATE as usize).for_each(drop);
+        assert!(
+            (handle.position() - 0.75).abs() < 0.05,
+            "{}",
+            handle.position()
+        );
+        stretch.try_seek(Duration::from_millis(2000)).unwrap();
+        assert_eq!(handle.position(), 2.0);
+        stretch.by_ref().take(RATE as usize * 2).for_each(drop);
+        assert!(
+            (handle.position() - 3.0).abs() < 0.05,
+            "{}",
+            handle.position()
+        );
+    }
+}
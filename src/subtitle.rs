@@ -0,0 +1,171 @@
+use crate::lrc::{parse_timestamp, LrcDocument, LrcLine};
+
+pub fn parse_srt(content: &str) -> LrcDocument {
+    parse_cues(content, false)
+}
+
+pub fn parse_vtt(content: &str) -> LrcDocument {
+    parse_cues(content, true)
+}
+
+fn parse_cues(content: &str, vtt: bool) -> LrcDocument {
+    let mut doc = LrcDocument::default();
+    let mut lines = content.lines().enumerate().peekable();
+    if vtt {
+        match lines.peek() {
+            Some((_, first)) if first.trim_start().starts_with("WEBVTT") => {
+                skip_block(&mut lines);
+            }
+            _ => doc.warn(1, "missing WEBVTT header"),
+        }
+    }
+    while let Some((num, line)) = lines.next() {
+        let num = num + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if vtt
+            && ["NOTE", "STYLE", "REGION"]
+                .iter()
+                .any(|k| line.starts_with(k))
+        {
+            skip_block(&mut lines);
+            continue;
+        }
+        let timing = if line.contains("-->") {
+            line
+        } else {
+            match lines.peek() {
+                Some((_, next)) if next.contains("-->") => {
+                    lines.next().map(|(_, next)| next.trim()).unwrap_or("")
+                }
+                _ => {
+                    doc.warn(num, "no cue timing, line skipped");
+                    continue;
+                }
+            }
+        };
+        let Some((start, end)) = parse_timing(timing) else {
+            doc.warn(num, "malformed cue timing, cue skipped");
+            skip_block(&mut lines);
+            continue;
+        };
+        let mut text = String::new();
+        while let Some((_, next)) = lines.peek() {
+            if next.trim().is_empty() {
+                break;
+            }
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            text.push_str(next.trim());
+            lines.next();
+        }
+        let text = clean_text(&text);
+        if text.trim().is_empty() {
+            continue;
+        }
+        let mut lyric = LrcLine::new(start, &text, num);
+        lyric.end = Some(end);
+        doc.lines.push(lyric);
+    }
+    doc.normalize();
+    doc
+}
+
+fn skip_block<'a>(lines: &mut impl Iterator<Item = (usize, &'a str)>) {
+    for (_, line) in lines {
+        if line.trim().is_empty() {
+            break;
+        }
+    }
+}
+
+fn parse_timing(line: &str) -> Option<(f32, f32)> {
+    let (start, rest) = line.split_once("-->")?;
+    let end = rest.split_whitespace().next()?;
+    Some((parse_cue_time(start)?, parse_cue_time(end)?))
+}
+
+fn parse_cue_time(text: &str) -> Option<f32> {
+    parse_timestamp(&text.trim().replace(',', "."))
+}
+
+fn clean_text(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(pos) = rest.find(['<', '{']) {
+        out.push_str(&rest[..pos]);
+        let close = if rest[pos..].starts_with('<') {
+            '>'
+        } else {
+            '}'
+        };
+        let Some(len) = rest[pos..].find(close) else {
+            rest = &rest[pos..];
+            break;
+        };
+        let tag = &rest[pos..pos + len + 1];
+        if close == '>' && parse_cue_time(&tag[1..tag.len() - 1]).is_some() {
+            out.push_str(tag);
+        }
+        rest = &rest[pos + len + 1..];
+    }
+    out.push_str(rest);
+    out.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cues(doc: &LrcDocument) -> Vec<(f32, Option<f32>, &str)> {
+        doc.lines
+            .iter()
+            .map(|l| (l.time, l.end, l.text.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn srt() {
+        let doc = parse_srt(
+            "1\n00:00:01,000 --> 00:00:02,500\nFirst <i>line</i>\n\n\
+             2\n00:00:03,000 --> 00:00:04,000\nSecond\nline\n\n\
+             3\nbroken --> timing\nSkipped\n",
+        );
+        assert_eq!(
+            cues(&doc),
+            [
+                (1.0, Some(2.5), "First line"),
+                (3.0, Some(4.0), "Second line")
+            ]
+        );
+        assert_eq!(doc.diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn vtt() {
+        let doc = parse_vtt(
+            "WEBVTT\n\nNOTE skipped\nstill a note\n\n\
+             intro\n00:01.000 --> 00:02.000 align:center\nFish &amp; chips\n\n\
+             00:00:03.000 --> 00:00:04.000\n<00:00:03.000>Word <00:00:03.500>timed\n",
+        );
+        assert_eq!(
+            cues(&doc),
+            [
+                (1.0, Some(2.0), "Fish & chips"),
+                (3.0, Some(4.0), "Word timed")
+            ]
+        );
+        assert_eq!(doc.lines[1].words.len(), 2);
+        assert!(doc.diagnostics.is_empty());
+        assert_eq!(
+            parse_vtt("00:01.000 --> 00:02.000\nHi\n").diagnostics.len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use crate::lrc::LrcLine;
+
+pub struct TapSync {
+    lines: Vec<(usize, String)>,
+    stamped: usize,
+}
+
+impl TapSync {
+    pub fn from_text(content: &str) -> Self {
+        let lines = content
+            .lines()
+            .enumerate()
+            .map(|(num, line)| (num + 1, line.trim().to_string()))
+            .filter(|(_, line)| !line.is_empty())
+            .collect();
+        Self { lines, stamped: 0 }
+    }
+
+    pub fn previous(&self) -> &str {
+        match self.stamped {
+            0 => "Press Enter as each line starts",
+            n => &self.lines[n - 1].1,
+        }
+    }
+
+    pub fn current(&self) -> &str {
+        self.lines.get(self.stamped).map_or("", |l| l.1.as_str())
+    }
+
+    pub fn upcoming(&self) -> &str {
+        self.lines
+            .get(self.stamped + 1)
+            .map_or("", |l| l.1.as_str())
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.stamped >= self.lines.len()
+    }
+
+    pub fn stamp(&mut self, time: f32) -> Option<LrcLine> {
+        let (num, text) = self.lines.get(self.stamped)?;
+        self.stamped += 1;
+        Some(LrcLine::new(time, text, *num))
+    }
+
+    pub fn undo(&mut self) -> bool {
+        if self.stamped == 0 {
+            return false;
+        }
+        self.stamped -= 1;
+        true
+    }
+}
@@ -0,0 +1,79 @@
+#[derive(Clone, Copy)]
+pub struct Theme {
+    pub bg: u32,
+    pub text: u32,
+    pub dim: u32,
+    pub accent: u32,
+    pub button: u32,
+    pub bar_bg: u32,
+    pub bar_fg: u32,
+    pub active: u32,
+    pub highlight: u32,
+}
+
+pub const DARK: Theme = Theme {
+    bg: 0x121212,
+    text: 0xf0f0f0,
+    dim: 0x606060,
+    accent: 0x909090,
+    button: 0x1e1e1e,
+    bar_bg: 0x252525,
+    bar_fg: 0x707070,
+    active: 0x4a9f4a,
+    highlight: 0x7ec8e3,
+};
+
+pub const HIGH_CONTRAST: Theme = Theme {
+    bg: 0x000000,
+    text: 0xffffff,
+    dim: 0xa0a0a0,
+    accent: 0xd8d8d8,
+    button: 0x202020,
+    bar_bg: 0x303030,
+    bar_fg: 0xffffff,
+    active: 0x3cff3c,
+    highlight: 0xffd400,
+};
+
+pub const LIGHT: Theme = Theme {
+    bg: 0xf4f4f4,
+    text: 0x141414,
+    dim: 0x8a8a8a,
+    accent: 0x505050,
+    button: 0xe2e2e2,
+    bar_bg: 0xd8d8d8,
+    bar_fg: 0x707070,
+    active: 0x2e7d32,
+    highlight: 0x1565c0,
+};
+
+impl Theme {
+    pub fn named(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "dark" => Some(DARK),
+            "high-contrast" | "high_contrast" => Some(HIGH_CONTRAST),
+            "light" => Some(LIGHT),
+            _ => None,
+        }
+    }
+
+    pub fn set_color(&mut self, key: &str, value: &str) -> bool {
+        let Ok(color) = u32::from_str_radix(value.trim().trim_start_matches('#'), 16) else {
+            return false;
+        };
+        let slot = match key {
+            "bg" => &mut self.bg,
+            "text" => &mut self.text,
+            "dim" => &mut self.dim,
+            "accent" => &mut self.accent,
+            "button" => &mut self.button,
+            "bar_bg" => &mut self.bar_bg,
+            "bar_fg" => &mut self.bar_fg,
+            "active" => &mut self.active,
+            "highlight" => &mut self.highlight,
+            _ => return false,
+        };
+        *slot = color & 0xffffff;
+        true
+    }
+}